@@ -0,0 +1,227 @@
+use crate::decode::{DecodedValue, ValueType, decode_field, decode_string};
+use crate::key::{MappingKey, parse_address};
+use crate::slot::keccak256;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A function parameter or return type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiType {
+    Value(ValueType),
+    String,
+    Bytes,
+}
+
+impl FromStr for AbiType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "string" => Ok(AbiType::String),
+            "bytes" => Ok(AbiType::Bytes),
+            _ => s.parse().map(AbiType::Value),
+        }
+    }
+}
+
+impl fmt::Display for AbiType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiType::Value(ValueType::Uint(bits)) => write!(f, "uint{}", bits),
+            AbiType::Value(ValueType::Int(bits)) => write!(f, "int{}", bits),
+            AbiType::Value(ValueType::Address) => write!(f, "address"),
+            AbiType::Value(ValueType::Bool) => write!(f, "bool"),
+            AbiType::Value(ValueType::FixedBytes(size)) => write!(f, "bytes{}", size),
+            AbiType::String => write!(f, "string"),
+            AbiType::Bytes => write!(f, "bytes"),
+        }
+    }
+}
+
+/// `name(inputs)(outputs)`, e.g. `balanceOf(address)(uint256)` or `getReserves()(uint112,uint112,uint32)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub inputs: Vec<AbiType>,
+    pub outputs: Vec<AbiType>,
+}
+
+impl Signature {
+    /// First 4 bytes of keccak256 of the canonical `name(type,...)`
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = keccak256(self.to_string().as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Calldata for static arguments, written like mapping keys (addresses, decimal or 0x numbers)
+    pub fn encode_call(&self, args: &[String]) -> Result<Vec<u8>> {
+        anyhow::ensure!(
+            args.len() == self.inputs.len(),
+            "{} takes {} argument(s), got {}",
+            self,
+            self.inputs.len(),
+            args.len()
+        );
+
+        let mut data = self.selector().to_vec();
+        for (ty, arg) in self.inputs.iter().zip(args) {
+            let key = match ty {
+                AbiType::Value(ValueType::Address) => MappingKey::Address(parse_address(arg)?),
+                AbiType::Value(_) => format!("{}:{}", ty, arg).parse()?,
+                _ => anyhow::bail!("Dynamic argument types are not supported: {}", ty),
+            };
+            data.extend(key.encode());
+        }
+        Ok(data)
+    }
+
+    /// Decode return data according to the declared outputs
+    pub fn decode_output(&self, data: &[u8]) -> Result<Vec<DecodedValue>> {
+        self.outputs
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| decode_output_at(data, i * 32, *ty))
+            .collect()
+    }
+}
+
+impl FromStr for Signature {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let open = s
+            .find('(')
+            .with_context(|| format!("Missing ( in signature {}", s))?;
+        let close = open
+            + s[open..]
+                .find(')')
+                .with_context(|| format!("Missing ) in signature {}", s))?;
+
+        let outputs = match s[close + 1..].trim() {
+            "" => "",
+            rest => rest
+                .strip_prefix('(')
+                .and_then(|r| r.strip_suffix(')'))
+                .with_context(|| format!("Invalid return types in {}", s))?,
+        };
+
+        Ok(Signature {
+            name: s[..open].trim().to_string(),
+            inputs: parse_types(&s[open + 1..close])?,
+            outputs: parse_types(outputs)?,
+        })
+    }
+}
+
+/// Canonical form used for the selector, without outputs
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inputs: Vec<String> = self.inputs.iter().map(|ty| ty.to_string()).collect();
+        write!(f, "{}({})", self.name, inputs.join(","))
+    }
+}
+
+fn parse_types(list: &str) -> Result<Vec<AbiType>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|ty| !ty.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+fn decode_output_at(data: &[u8], position: usize, ty: AbiType) -> Result<DecodedValue> {
+    let head = word_at(data, position)?;
+    match ty {
+        // ABI pads bytesN on the right, unlike packed storage
+        AbiType::Value(value_type @ ValueType::FixedBytes(size)) => {
+            decode_field(&head, 32 - size, None, value_type)
+        }
+        AbiType::Value(value_type) => decode_field(&head, 0, None, value_type),
+        AbiType::String | AbiType::Bytes => {
+            let offset = word_to_usize(&head)?;
+            let len = word_to_usize(&word_at(data, offset)?)?;
+            let bytes = offset
+                .checked_add(32)
+                .and_then(|start| data.get(start..start.checked_add(len)?))
+                .context("Return data shorter than its length")?
+                .to_vec();
+            Ok(match ty {
+                AbiType::String => decode_string(bytes),
+                _ => DecodedValue::Bytes(bytes),
+            })
+        }
+    }
+}
+
+fn word_at(data: &[u8], position: usize) -> Result<[u8; 32]> {
+    let slice = position
+        .checked_add(32)
+        .and_then(|end| data.get(position..end))
+        .with_context(|| format!("Return data too short for a word at {}", position))?;
+    let mut word = [0u8; 32];
+    word.copy_from_slice(slice);
+    Ok(word)
+}
+
+fn word_to_usize(word: &[u8; 32]) -> Result<usize> {
+    anyhow::ensure!(
+        word[..24].iter().all(|b| *b == 0),
+        "Offset or length does not fit in 64 bits"
+    );
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&word[24..]);
+    usize::try_from(u64::from_be_bytes(raw)).context("Offset or length too large")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_balance_of() -> Result<()> {
+        let sig: Signature = "balanceOf(address)(uint256)".parse()?;
+        assert_eq!(hex::encode(sig.selector()), "70a08231");
+
+        let data = sig.encode_call(&["0xF977814e90dA44bFA03b6295A0616a897441aceC".to_string()])?;
+        assert_eq!(
+            hex::encode(data),
+            "70a08231000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec"
+        );
+
+        // `uint` canonicalizes to uint256 in the selector
+        let transfer: Signature = "transfer(address, uint)".parse()?;
+        assert_eq!(transfer.to_string(), "transfer(address,uint256)");
+        assert_eq!(hex::encode(transfer.selector()), "a9059cbb");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_static_and_string_outputs() -> Result<()> {
+        let reserves: Signature = "getReserves()(uint112,uint112,uint32)".parse()?;
+        let mut data = vec![0u8; 96];
+        data[31] = 0x64;
+        data[63] = 0xc8;
+        data[95] = 0x01;
+        let values: Vec<String> = reserves
+            .decode_output(&data)?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(values, vec!["100", "200", "1"]);
+
+        // offset 0x20, length 13, "Wrapped Ether"
+        let name: Signature = "name()(string)".parse()?;
+        let mut data = vec![0u8; 96];
+        data[31] = 0x20;
+        data[63] = 13;
+        data[64..77].copy_from_slice(b"Wrapped Ether");
+        assert_eq!(
+            name.decode_output(&data)?,
+            vec![DecodedValue::String("Wrapped Ether".to_string())]
+        );
+        assert!(name.decode_output(&data[..64]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use crate::rpc::{BlockId, ErrorObject, JsonRpcRequest, RpcError, parse_response};
+use crate::slot::Slot;
+use anyhow::{Context, Result};
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+
+/// One `eth_getStorageAt` read inside a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageRequest {
+    pub address: String,
+    pub slot: Slot,
+    pub block: BlockId,
+}
+
+/// How a batch is split into HTTP requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Calls per JSON-RPC batch array (providers often cap this at 100-1000)
+    pub chunk_size: usize,
+    /// Batch arrays in flight at once
+    pub concurrency: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 100,
+            concurrency: 4,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct BatchResponse {
+    id: u64,
+    result: Option<serde_json::Value>,
+    error: Option<ErrorObject>,
+}
+
+/// Read many slots through JSON-RPC batch arrays over a single client.
+/// Results are in input order, one `Result` per request.
+pub async fn get_storage_batch(
+    rpc_url: &str,
+    requests: &[StorageRequest],
+    options: BatchOptions,
+) -> Vec<Result<String>> {
+    let calls = requests
+        .iter()
+        .map(|request| {
+            (
+                "eth_getStorageAt",
+                serde_json::json!([
+                    request.address,
+                    request.slot.to_string(),
+                    request.block.to_param()
+                ]),
+            )
+        })
+        .collect();
+
+    batch_call(rpc_url, calls, options)
+        .await
+        .into_iter()
+        .map(|result| {
+            result.and_then(|value| {
+                serde_json::from_value(value).context("Unexpected eth_getStorageAt result")
+            })
+        })
+        .collect()
+}
+
+/// Send `(method, params)` calls as batch arrays, returning each raw `result` in input order
+pub(crate) async fn batch_call(
+    rpc_url: &str,
+    calls: Vec<(&str, serde_json::Value)>,
+    options: BatchOptions,
+) -> Vec<Result<serde_json::Value>> {
+    let client = reqwest::Client::new();
+    let chunk_size = options.chunk_size.max(1);
+
+    // Ids are global indices so each response finds its slot in the output
+    let requests: Vec<JsonRpcRequest> = calls
+        .into_iter()
+        .enumerate()
+        .map(|(id, (method, params))| JsonRpcRequest::new(method, params, id as u64))
+        .collect();
+
+    let chunks: Vec<Vec<Result<serde_json::Value>>> = stream::iter(
+        requests
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| send_chunk(&client, rpc_url, i * chunk_size, chunk)),
+    )
+    .buffered(options.concurrency.max(1))
+    .collect()
+    .await;
+
+    chunks.into_iter().flatten().collect()
+}
+
+async fn send_chunk(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    start: usize,
+    chunk: &[JsonRpcRequest],
+) -> Vec<Result<serde_json::Value>> {
+    match post_batch(client, rpc_url, chunk).await {
+        Ok(responses) => match_responses(start, chunk.len(), responses),
+        // A failed HTTP request fails every call it carried
+        Err(e) => (0..chunk.len()).map(|_| Err(e.clone().into())).collect(),
+    }
+}
+
+async fn post_batch(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    chunk: &[JsonRpcRequest],
+) -> Result<Vec<BatchResponse>, RpcError> {
+    let response = client
+        .post(rpc_url)
+        .json(chunk)
+        .send()
+        .await
+        .map_err(|e| RpcError::transport(&e))?;
+    let status = response.status().as_u16();
+    let body = response.text().await.map_err(|e| RpcError::transport(&e))?;
+
+    if let Ok(responses) = serde_json::from_str(&body) {
+        return Ok(responses);
+    }
+    // Providers rejecting the whole batch (too large, unsupported) answer with one error object
+    parse_response(status, &body)?;
+    Err(RpcError::Decode(
+        "Expected a batch response array".to_string(),
+    ))
+}
+
+// Responses may arrive in any order, match them back by id
+fn match_responses(
+    start: usize,
+    len: usize,
+    responses: Vec<BatchResponse>,
+) -> Vec<Result<serde_json::Value>> {
+    let mut results: Vec<Option<Result<serde_json::Value>>> = (0..len).map(|_| None).collect();
+
+    for response in responses {
+        let entry = usize::try_from(response.id)
+            .ok()
+            .and_then(|id| id.checked_sub(start))
+            .and_then(|i| results.get_mut(i));
+        let Some(entry) = entry else {
+            continue;
+        };
+
+        *entry = Some(match response.error {
+            Some(error) => Err(RpcError::from(error).into()),
+            None => Ok(response.result.unwrap_or(serde_json::Value::Null)),
+        });
+    }
+
+    results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                Err(RpcError::Decode("Missing response in RPC batch".to_string()).into())
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::tests::mock_server;
+    use std::sync::{Arc, Mutex};
+
+    fn responses(json: serde_json::Value) -> Result<Vec<BatchResponse>> {
+        Ok(serde_json::from_value(json)?)
+    }
+
+    #[test]
+    fn test_responses_matched_by_id() -> Result<()> {
+        // Chunk starting at global index 100, answered out of order with one error and one gap
+        let body = responses(serde_json::json!([
+            { "jsonrpc": "2.0", "id": 102, "result": "0x02" },
+            { "jsonrpc": "2.0", "id": 100, "result": "0x00" },
+            { "jsonrpc": "2.0", "id": 101, "error": { "code": -32000, "message": "missing trie node" } },
+            { "jsonrpc": "2.0", "id": 7, "result": "0xff" }
+        ]))?;
+
+        let results = match_responses(100, 4, body);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().ok(), Some(&serde_json::json!("0x00")));
+        assert!(results[1].as_ref().is_err_and(|e| {
+            e.downcast_ref::<RpcError>()
+                .is_some_and(RpcError::is_missing_state)
+        }));
+        assert_eq!(results[2].as_ref().ok(), Some(&serde_json::json!("0x02")));
+        assert!(results[3].is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transport_failure_is_per_item() {
+        let requests: Vec<StorageRequest> = (0..5)
+            .map(|i| StorageRequest {
+                address: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
+                slot: Slot::from(i),
+                block: BlockId::Latest,
+            })
+            .collect();
+        let options = BatchOptions {
+            chunk_size: 2,
+            concurrency: 2,
+        };
+
+        let results = get_storage_batch("http://127.0.0.1:1", &requests, options).await;
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|result| {
+            result.as_ref().is_err_and(|e| {
+                matches!(e.downcast_ref::<RpcError>(), Some(RpcError::Transport(_)))
+            })
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_chunks_answered_out_of_order() -> Result<()> {
+        // Node answering each batch reversed, echoing the slot, with slot 3 failing
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&sizes);
+        let url = mock_server(move |body| {
+            let calls = body.as_array().cloned().unwrap_or_default();
+            if let Ok(mut seen) = seen.lock() {
+                seen.push(calls.len());
+            }
+            let replies = calls.iter().rev().map(|call| {
+                let slot = call["params"][1].clone();
+                if slot == serde_json::json!(Slot::from(3).to_string()) {
+                    serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "error": { "code": -32000, "message": "missing trie node" },
+                    })
+                } else {
+                    serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": slot })
+                }
+            });
+            serde_json::Value::Array(replies.collect())
+        })
+        .await?;
+
+        let requests: Vec<StorageRequest> = (0..7)
+            .map(|i| StorageRequest {
+                address: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
+                slot: Slot::from(i),
+                block: BlockId::Latest,
+            })
+            .collect();
+        let options = BatchOptions {
+            chunk_size: 3,
+            concurrency: 2,
+        };
+        let results = get_storage_batch(&url, &requests, options).await;
+
+        let mut sizes = sizes
+            .lock()
+            .map_err(|_| anyhow::anyhow!("poisoned"))?
+            .clone();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 3, 3]);
+        assert_eq!(results.len(), 7);
+        for (i, result) in results.iter().enumerate() {
+            if i == 3 {
+                assert!(result.as_ref().is_err_and(|e| {
+                    e.downcast_ref::<RpcError>()
+                        .is_some_and(RpcError::is_missing_state)
+                }));
+            } else {
+                assert_eq!(
+                    result.as_ref().ok(),
+                    Some(&Slot::from(i as u64).to_string())
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,567 @@
+use crate::rpc::{BlockId, call};
+use crate::slot::{Compiler, Slot};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+const STOP: u8 = 0x00;
+const ADD: u8 = 0x01;
+const EQ: u8 = 0x14;
+const KECCAK256: u8 = 0x20;
+const CALLDATACOPY: u8 = 0x37;
+const CODECOPY: u8 = 0x39;
+const EXTCODECOPY: u8 = 0x3c;
+const RETURNDATACOPY: u8 = 0x3e;
+const MSTORE: u8 = 0x52;
+const MSTORE8: u8 = 0x53;
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+const JUMP: u8 = 0x56;
+const JUMPI: u8 = 0x57;
+const JUMPDEST: u8 = 0x5b;
+const MCOPY: u8 = 0x5e;
+const PUSH0: u8 = 0x5f;
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const DUP16: u8 = 0x8f;
+const SWAP1: u8 = 0x90;
+const SWAP16: u8 = 0x9f;
+const CALL: u8 = 0xf1;
+const CALLCODE: u8 = 0xf2;
+const RETURN: u8 = 0xf3;
+const DELEGATECALL: u8 = 0xf4;
+const STATICCALL: u8 = 0xfa;
+const REVERT: u8 = 0xfd;
+const INVALID: u8 = 0xfe;
+const SELFDESTRUCT: u8 = 0xff;
+
+/// One decoded opcode with its PUSH immediate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub pc: usize,
+    pub opcode: u8,
+    pub immediate: Vec<u8>,
+}
+
+impl Instruction {
+    /// The pushed constant for PUSH0..PUSH32
+    pub fn push_value(&self) -> Option<Slot> {
+        if !(PUSH0..=PUSH32).contains(&self.opcode) {
+            return None;
+        }
+        let mut word = [0u8; 32];
+        word[32 - self.immediate.len()..].copy_from_slice(&self.immediate);
+        Some(Slot(word))
+    }
+
+    // Control never falls through to the next instruction
+    fn ends_flow(&self) -> bool {
+        matches!(
+            self.opcode,
+            STOP | JUMP | RETURN | REVERT | INVALID | SELFDESTRUCT
+        )
+    }
+}
+
+/// Split bytecode into instructions, a PUSH cut off by the end of the code is zero-padded
+pub fn disassemble(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let width = match opcode {
+            PUSH1..=PUSH32 => (opcode - PUSH0) as usize,
+            _ => 0,
+        };
+        let end = (pc + 1 + width).min(code.len());
+        let mut immediate = code[pc + 1..end].to_vec();
+        immediate.resize(width, 0);
+        instructions.push(Instruction {
+            pc,
+            opcode,
+            immediate,
+        });
+        pc += 1 + width;
+    }
+    instructions
+}
+
+/// How a storage access computes its slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotPattern {
+    /// Constant slot, a plain (possibly packed) variable
+    Variable(Slot),
+    /// keccak256 of a key and a constant position
+    Mapping { position: Slot, compiler: Compiler },
+    /// keccak256 of a constant position alone, dynamic array data
+    DynamicArray(Slot),
+}
+
+impl fmt::Display for SlotPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotPattern::Variable(slot) => write!(f, "variable {}", slot),
+            SlotPattern::Mapping {
+                position,
+                compiler: Compiler::Solidity,
+            } => write!(f, "mapping at {}", position),
+            SlotPattern::Mapping { position, .. } => write!(f, "vyper HashMap at {}", position),
+            SlotPattern::DynamicArray(position) => write!(f, "dynamic array at {}", position),
+        }
+    }
+}
+
+/// A storage location the bytecode touches, with the functions that reach it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotCandidate {
+    pub pattern: SlotPattern,
+    pub reads: usize,
+    pub writes: usize,
+    pub selectors: BTreeSet<[u8; 4]>,
+}
+
+/// Deployed bytecode of `address`
+pub async fn get_code(rpc_url: &str, address: &str, block: BlockId) -> Result<Vec<u8>> {
+    let raw: String = call(
+        rpc_url,
+        "eth_getCode",
+        serde_json::json!([address, block.to_param()]),
+    )
+    .await?;
+    hex::decode(raw.trim_start_matches("0x")).with_context(|| format!("Invalid code: {}", raw))
+}
+
+/// Guess the storage layout from bytecode: every SLOAD / SSTORE whose slot is a constant
+/// or a keccak256 of constant positions, ranked by how often it is accessed.
+/// Heuristic: slots computed across jumps (internal helpers, via-IR) are not followed.
+pub fn analyze_bytecode(code: &[u8]) -> Vec<SlotCandidate> {
+    let instructions = disassemble(code);
+    let reach = selector_reach(&instructions);
+
+    let mut candidates: Vec<SlotCandidate> = Vec::new();
+    for (block_start, pattern, is_write) in storage_accesses(&instructions) {
+        let index = match candidates.iter().position(|c| c.pattern == pattern) {
+            Some(index) => index,
+            None => {
+                candidates.push(SlotCandidate {
+                    pattern,
+                    reads: 0,
+                    writes: 0,
+                    selectors: BTreeSet::new(),
+                });
+                candidates.len() - 1
+            }
+        };
+        let candidate = &mut candidates[index];
+        if is_write {
+            candidate.writes += 1;
+        } else {
+            candidate.reads += 1;
+        }
+        if let Some(selectors) = reach.get(&block_start) {
+            candidate.selectors.extend(selectors);
+        }
+    }
+
+    // Stable: equally ranked candidates keep bytecode order
+    candidates.sort_by_key(|c| std::cmp::Reverse((c.reads + c.writes, c.selectors.len())));
+    candidates
+}
+
+// Symbolic stack / memory value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Const(Slot),
+    Derived(SlotPattern),
+    Unknown,
+}
+
+#[derive(Default)]
+struct Machine {
+    stack: Vec<Value>,
+    memory: BTreeMap<u64, Value>,
+}
+
+impl Machine {
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap_or(Value::Unknown)
+    }
+
+    // Make the top `depth` items addressable, padding unseen items as Unknown
+    fn reserve(&mut self, depth: usize) {
+        while self.stack.len() < depth {
+            self.stack.insert(0, Value::Unknown);
+        }
+    }
+
+    // `depth` items below the top, without popping
+    fn peek(&self, depth: usize) -> Value {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .map_or(Value::Unknown, |i| self.stack[i])
+    }
+
+    // Forget every tracked word overlapping [offset, offset + size), all of them if unknown
+    fn clobber(&mut self, offset: Value, size: Value) {
+        let range = match (offset, size) {
+            (Value::Const(offset), Value::Const(size)) => offset.to_u64().zip(size.to_u64()),
+            _ => None,
+        };
+        match range {
+            Some((_, 0)) => {}
+            Some((start, size)) => {
+                let end = start.saturating_add(size);
+                self.memory
+                    .retain(|at, _| at.saturating_add(32) <= start || *at >= end);
+            }
+            None => self.memory.clear(),
+        }
+    }
+
+    // (offset, size) of the memory an opcode other than MSTORE writes, read before it runs
+    fn memory_write(&self, op: u8) -> Option<(Value, Value)> {
+        match op {
+            MSTORE8 => Some((self.peek(0), Value::Const(Slot::from(1)))),
+            CALLDATACOPY | CODECOPY | RETURNDATACOPY | MCOPY => Some((self.peek(0), self.peek(2))),
+            EXTCODECOPY => Some((self.peek(1), self.peek(3))),
+            CALL | CALLCODE => Some((self.peek(5), self.peek(6))),
+            DELEGATECALL | STATICCALL => Some((self.peek(4), self.peek(5))),
+            _ => None,
+        }
+    }
+
+    fn hash(&self, offset: Value, size: Value) -> Value {
+        let (Value::Const(offset), Value::Const(size)) = (offset, size) else {
+            return Value::Unknown;
+        };
+        let (Some(offset), Some(size)) = (offset.to_u64(), size.to_u64()) else {
+            return Value::Unknown;
+        };
+        // Offsets come from untrusted PUSH operands, a word past u64::MAX is simply unknown
+        let word = |at: Option<u64>| {
+            at.and_then(|at| self.memory.get(&at).copied())
+                .unwrap_or(Value::Unknown)
+        };
+        let first = word(Some(offset));
+        match size {
+            64 => match (first, word(offset.checked_add(32))) {
+                // Solidity keccak256(key . position), nested mappings keep the outer pattern
+                (_, Value::Const(position)) => Value::Derived(SlotPattern::Mapping {
+                    position,
+                    compiler: Compiler::Solidity,
+                }),
+                (_, Value::Derived(pattern)) => Value::Derived(pattern),
+                // Vyper keccak256(position . key)
+                (Value::Const(position), _) => Value::Derived(SlotPattern::Mapping {
+                    position,
+                    compiler: Compiler::Vyper,
+                }),
+                (Value::Derived(pattern), _) => Value::Derived(pattern),
+                _ => Value::Unknown,
+            },
+            32 => match first {
+                Value::Const(position) => Value::Derived(SlotPattern::DynamicArray(position)),
+                _ => Value::Unknown,
+            },
+            _ => Value::Unknown,
+        }
+    }
+}
+
+// Every SLOAD / SSTORE with a recognizable slot: (basic block start, pattern, is write)
+fn storage_accesses(instructions: &[Instruction]) -> Vec<(usize, SlotPattern, bool)> {
+    let mut accesses = Vec::new();
+    let mut machine = Machine::default();
+    let mut block_start = 0;
+
+    for instruction in instructions {
+        let op = instruction.opcode;
+        if op == JUMPDEST {
+            machine = Machine::default();
+            block_start = instruction.pc;
+        }
+
+        if let Some((offset, size)) = machine.memory_write(op) {
+            machine.clobber(offset, size);
+        }
+
+        match op {
+            PUSH0..=PUSH32 => {
+                let value = instruction
+                    .push_value()
+                    .map_or(Value::Unknown, Value::Const);
+                machine.stack.push(value);
+            }
+            DUP1..=DUP16 => {
+                let depth = (op - DUP1 + 1) as usize;
+                machine.reserve(depth);
+                let value = machine.stack[machine.stack.len() - depth];
+                machine.stack.push(value);
+            }
+            SWAP1..=SWAP16 => {
+                let depth = (op - SWAP1 + 1) as usize;
+                machine.reserve(depth + 1);
+                let top = machine.stack.len() - 1;
+                machine.stack.swap(top, top - depth);
+            }
+            ADD => {
+                // Struct members and array elements keep the variable they belong to
+                let value = match (machine.pop(), machine.pop()) {
+                    (Value::Const(a), Value::Const(b)) => Value::Const(a.wrapping_add(&b)),
+                    (Value::Derived(pattern), Value::Const(_))
+                    | (Value::Const(_), Value::Derived(pattern)) => Value::Derived(pattern),
+                    _ => Value::Unknown,
+                };
+                machine.stack.push(value);
+            }
+            MSTORE => {
+                let (offset, value) = (machine.pop(), machine.pop());
+                machine.clobber(offset, Value::Const(Slot::from(32)));
+                match offset {
+                    Value::Const(offset) => match offset.to_u64() {
+                        Some(offset) => {
+                            machine.memory.insert(offset, value);
+                        }
+                        None => machine.memory.clear(),
+                    },
+                    _ => machine.memory.clear(),
+                }
+            }
+            KECCAK256 => {
+                let (offset, size) = (machine.pop(), machine.pop());
+                let value = machine.hash(offset, size);
+                machine.stack.push(value);
+            }
+            SLOAD | SSTORE => {
+                let slot = machine.pop();
+                let pattern = match slot {
+                    Value::Const(slot) => Some(SlotPattern::Variable(slot)),
+                    Value::Derived(pattern) => Some(pattern),
+                    Value::Unknown => None,
+                };
+                if let Some(pattern) = pattern {
+                    accesses.push((block_start, pattern, op == SSTORE));
+                }
+                if op == SSTORE {
+                    machine.pop();
+                } else {
+                    machine.stack.push(Value::Unknown);
+                }
+            }
+            _ => {
+                let (pops, pushes) = stack_effect(op);
+                for _ in 0..pops {
+                    machine.pop();
+                }
+                machine
+                    .stack
+                    .extend(std::iter::repeat_n(Value::Unknown, pushes));
+            }
+        }
+
+        if instruction.ends_flow() {
+            machine = Machine::default();
+        }
+    }
+    accesses
+}
+
+// (inputs, outputs) of opcodes without symbolic handling
+fn stack_effect(op: u8) -> (usize, usize) {
+    match op {
+        0x02..=0x07 | 0x0a | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d => (2, 1),
+        0x08 | 0x09 => (3, 1),
+        0x15 | 0x19 => (1, 1),
+        0x31 | 0x35 | 0x3b | 0x3f | 0x40 | 0x49 | 0x51 | 0x5c => (1, 1),
+        0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d | 0x41..=0x48 | 0x4a | 0x58..=0x5a => (0, 1),
+        0x37 | 0x39 | 0x3e | 0x5e => (3, 0),
+        0x3c => (4, 0),
+        0x50 | 0x56 | 0xff => (1, 0),
+        0x53 | 0x57 | 0x5d | 0xf3 | 0xfd => (2, 0),
+        0xa0..=0xa4 => (2 + (op - 0xa0) as usize, 0),
+        0xf0 => (3, 1),
+        0xf1 | 0xf2 => (7, 1),
+        0xf4 | 0xfa => (6, 1),
+        0xf5 => (4, 1),
+        _ => (0, 0),
+    }
+}
+
+// Function selectors from the dispatcher: PUSH4 selector, [DUPn], EQ, PUSHn dest, JUMPI
+fn dispatch_table(instructions: &[Instruction]) -> Vec<([u8; 4], usize)> {
+    let mut table = Vec::new();
+    for (i, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode != PUSH4 {
+            continue;
+        }
+        let mut rest = &instructions[i + 1..];
+        if rest
+            .first()
+            .is_some_and(|ins| (DUP1..=DUP16).contains(&ins.opcode))
+        {
+            rest = &rest[1..];
+        }
+        let [eq, dest, jumpi, ..] = rest else {
+            continue;
+        };
+        if eq.opcode != EQ || jumpi.opcode != JUMPI {
+            continue;
+        }
+        if let Some(dest) = dest.push_value().and_then(|v| v.to_u64()) {
+            let mut selector = [0u8; 4];
+            selector.copy_from_slice(&instruction.immediate);
+            table.push((selector, dest as usize));
+        }
+    }
+    table
+}
+
+// JUMPDEST-delimited block start -> selectors whose entry can reach it. Successors are the
+// fallthrough and every JUMPDEST pushed inside the block (jump targets and return addresses).
+fn selector_reach(instructions: &[Instruction]) -> HashMap<usize, BTreeSet<[u8; 4]>> {
+    let jumpdests: BTreeSet<usize> = instructions
+        .iter()
+        .filter(|ins| ins.opcode == JUMPDEST)
+        .map(|ins| ins.pc)
+        .collect();
+
+    let mut successors: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut block_start = 0;
+    for (i, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode == JUMPDEST && instruction.pc != block_start {
+            if i > 0 && !instructions[i - 1].ends_flow() {
+                successors
+                    .entry(block_start)
+                    .or_default()
+                    .push(instruction.pc);
+            }
+            block_start = instruction.pc;
+        }
+        if let Some(target) = instruction.push_value().and_then(|v| v.to_u64())
+            && jumpdests.contains(&(target as usize))
+        {
+            successors
+                .entry(block_start)
+                .or_default()
+                .push(target as usize);
+        }
+    }
+
+    let mut reach: HashMap<usize, BTreeSet<[u8; 4]>> = HashMap::new();
+    for (selector, entry) in dispatch_table(instructions) {
+        let mut seen = BTreeSet::new();
+        let mut queue = vec![entry];
+        while let Some(block) = queue.pop() {
+            if !seen.insert(block) {
+                continue;
+            }
+            reach.entry(block).or_default().insert(selector);
+            queue.extend(successors.get(&block).into_iter().flatten());
+        }
+    }
+    reach
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Legacy solc shaped contract:
+    //   dispatcher: balanceOf(address) -> 0x1b, totalSupply() -> 0x35
+    //   0x1b: SLOAD keccak256(calldata[4] . 3), a balance mapping at position 3
+    //   0x35: SLOAD 2 then SSTORE 2 (a counter), SLOAD keccak256(5) (array data)
+    const CODE: &str = concat!(
+        "60003560e01c",
+        "806370a0823114601b57",
+        "806318160ddd14603557",
+        "00",
+        "5b600435600052600360205260406000205460005260206000f3",
+        "5b60025480600101600255600560005260206000205400",
+    );
+
+    #[test]
+    fn test_mapping_and_variable_candidates() -> Result<()> {
+        let code = hex::decode(CODE)?;
+        let candidates = analyze_bytecode(&code);
+        assert_eq!(candidates[0].pattern, SlotPattern::Variable(Slot::from(2)));
+        assert_eq!((candidates[0].reads, candidates[0].writes), (1, 1));
+        assert!(candidates[0].selectors.contains(&[0x18, 0x16, 0x0d, 0xdd]));
+
+        let mapping = candidates
+            .iter()
+            .find(|c| matches!(c.pattern, SlotPattern::Mapping { .. }))
+            .context("no mapping candidate")?;
+        assert_eq!(
+            mapping.pattern,
+            SlotPattern::Mapping {
+                position: Slot::from(3),
+                compiler: Compiler::Solidity
+            }
+        );
+        assert_eq!(
+            mapping.selectors.iter().collect::<Vec<_>>(),
+            vec![&[0x70, 0xa0, 0x82, 0x31]]
+        );
+        assert!(
+            candidates
+                .iter()
+                .any(|c| c.pattern == SlotPattern::DynamicArray(Slot::from(5)))
+        );
+        Ok(())
+    }
+
+    // mem[0x20] = 3, `clobber`, SLOAD keccak256(mem[0..0x40])
+    fn mapping_after(clobber: &str) -> Result<Vec<SlotCandidate>> {
+        let code = format!("6003602052{}60406000205400", clobber);
+        Ok(analyze_bytecode(&hex::decode(code)?))
+    }
+
+    #[test]
+    fn test_memory_writes_invalidate_hash_inputs() -> Result<()> {
+        let is_mapping = |candidates: &[SlotCandidate]| {
+            candidates
+                .iter()
+                .any(|c| matches!(c.pattern, SlotPattern::Mapping { .. }))
+        };
+        assert!(is_mapping(&mapping_after("")?));
+
+        for (name, clobber) in [
+            ("MSTORE8", "60ff603f53"),
+            ("CALLDATACOPY", "60206000602037"),
+            ("CODECOPY", "60206000602039"),
+            ("RETURNDATACOPY", "6020600060203e"),
+            ("MCOPY", "6020600060205e"),
+            ("unaligned MSTORE", "6000601052"),
+        ] {
+            assert!(
+                !is_mapping(&mapping_after(clobber)?),
+                "{} kept a stale word",
+                name
+            );
+        }
+        // Writes elsewhere keep the position word
+        assert!(is_mapping(&mapping_after("60206000604037")?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_offset_overflow_is_unknown() -> Result<()> {
+        // KECCAK256(offset = u64::MAX, size = 64) must neither panic nor wrap to 0x1f
+        let code = hex::decode("6003602052604067ffffffffffffffff205400")?;
+        assert!(analyze_bytecode(&code).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_disassemble_push_immediates() {
+        let instructions = disassemble(&[0x60, 0x03, 0x54, 0x61, 0xff]);
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[1].pc, 2);
+        // Truncated PUSH2 is zero-padded
+        assert_eq!(instructions[2].immediate, vec![0xff, 0x00]);
+        assert_eq!(instructions[0].push_value(), Some(Slot::from(3)));
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use rpc_surgeon::{
+    BatchOptions, BlockId, Compiler, Slot, ValueType, decode_field, holder_balances, parse_holders,
+    resolve_block,
+};
+use std::path::PathBuf;
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct BalancesArgs {
+    /// ERC-20 token address
+    #[arg(short, long)]
+    contract: String,
+
+    /// Position of the balance mapping (see `discover`)
+    #[arg(short, long)]
+    slot: Slot,
+
+    /// Holder list: one address per line, CSV with the address first, or a JSON array
+    #[arg(long)]
+    holders: PathBuf,
+
+    /// Storage convention of the token (solidity, vyper)
+    #[arg(long, default_value = "solidity")]
+    compiler: Compiler,
+
+    /// Type of the balance value
+    #[arg(short = 't', long = "type", default_value = "uint256")]
+    value_type: ValueType,
+
+    /// Block to snapshot at, a tag is pinned to one block number so every batch reads the same state
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+
+    #[arg(short, long, value_enum, default_value = "csv")]
+    format: Format,
+
+    /// Write the report to a file instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Reads per JSON-RPC batch
+    #[arg(long, default_value_t = 100)]
+    chunk_size: usize,
+
+    /// Batches in flight at once
+    #[arg(long, default_value_t = 4)]
+    concurrency: usize,
+}
+
+pub async fn run(args: BalancesArgs, rpc_url: &str) -> Result<()> {
+    let text = std::fs::read_to_string(&args.holders)
+        .with_context(|| format!("Failed to read {}", args.holders.display()))?;
+    let holders = parse_holders(&text)?;
+
+    let options = BatchOptions {
+        chunk_size: args.chunk_size,
+        concurrency: args.concurrency,
+    };
+    // One snapshot: pin `latest` before the batches run concurrently
+    let block = resolve_block(rpc_url, args.block).await?;
+    let balances = holder_balances(
+        rpc_url,
+        &args.contract,
+        args.slot,
+        &holders,
+        args.compiler,
+        block,
+        options,
+    )
+    .await?;
+
+    let mut rows = Vec::with_capacity(balances.len());
+    let mut failed = 0;
+    for balance in &balances {
+        let decoded = balance
+            .word
+            .as_ref()
+            .map_err(|e| format!("{:#}", e))
+            .and_then(|word| {
+                decode_field(word, 0, None, args.value_type)
+                    .map(|value| (format!("0x{}", hex::encode(word)), value.to_string()))
+                    .map_err(|e| format!("{:#}", e))
+            });
+        if decoded.is_err() {
+            failed += 1;
+        }
+        rows.push((
+            format!("0x{}", hex::encode(balance.holder)),
+            balance.slot.to_string(),
+            decoded,
+        ));
+    }
+
+    let report = match args.format {
+        Format::Csv => {
+            let mut out = String::from("address,slot,raw,value,error\n");
+            for (address, slot, decoded) in &rows {
+                let line = match decoded {
+                    Ok((raw, value)) => format!("{},{},{},{},\n", address, slot, raw, value),
+                    Err(e) => format!("{},{},,,\"{}\"\n", address, slot, e.replace('"', "'")),
+                };
+                out.push_str(&line);
+            }
+            out
+        }
+        Format::Json => {
+            let entries: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|(address, slot, decoded)| match decoded {
+                    Ok((raw, value)) => serde_json::json!({
+                        "address": address, "slot": slot, "raw": raw, "value": value
+                    }),
+                    Err(e) => serde_json::json!({
+                        "address": address, "slot": slot, "error": e
+                    }),
+                })
+                .collect();
+            let report = serde_json::json!({
+                "token": args.contract,
+                "block": block.to_string(),
+                "balances": entries,
+            });
+            serde_json::to_string_pretty(&report)? + "\n"
+        }
+    };
+
+    match &args.out {
+        Some(path) => std::fs::write(path, report)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", report),
+    }
+    eprintln!(
+        "{} holders read at block {}, {} failed",
+        balances.len(),
+        block,
+        failed
+    );
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use rpc_surgeon::{
+    BlockId, Compiler, MappingKey, Signature, Slot, StateOverride, derive_slot_path_for,
+    eth_call_with_overrides,
+};
+use std::str::FromStr;
+
+/// `[ADDRESS@]POSITION[key]...=VALUE`, e.g. `9[0xHOLDER]=1000000000000`
+#[derive(Debug, Clone)]
+struct OverrideSpec {
+    address: Option<String>,
+    position: Slot,
+    keys: Vec<MappingKey>,
+    value: Slot,
+}
+
+impl FromStr for OverrideSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (target, value) = s
+            .rsplit_once('=')
+            .with_context(|| format!("Override needs =VALUE: {}", s))?;
+        let (address, path) = match target.split_once('@') {
+            Some((address, path)) => (Some(address.to_string()), path),
+            None => (None, target),
+        };
+
+        let end = path.find('[').unwrap_or(path.len());
+        let mut keys = Vec::new();
+        let mut rest = &path[end..];
+        while let Some(inner) = rest.strip_prefix('[') {
+            let close = inner
+                .find(']')
+                .with_context(|| format!("Unclosed [ in {}", s))?;
+            keys.push(inner[..close].trim().parse()?);
+            rest = &inner[close + 1..];
+        }
+        anyhow::ensure!(rest.is_empty(), "Unexpected {} in {}", rest, s);
+
+        Ok(OverrideSpec {
+            address,
+            position: path[..end].trim().parse()?,
+            keys,
+            value: value.trim().parse()?,
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CallArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Function with return types, e.g. `balanceOf(address)(uint256)`
+    #[arg(long)]
+    sig: String,
+
+    /// Function arguments, in order (negative numbers for intN parameters)
+    #[arg(allow_negative_numbers = true)]
+    args: Vec<String>,
+
+    /// Storage override `[ADDRESS@]POSITION[key]...=VALUE` (repeat), the address defaults to --contract
+    #[arg(long = "set")]
+    overrides: Vec<OverrideSpec>,
+
+    /// Storage convention used to derive override slots (solidity, vyper)
+    #[arg(long, default_value = "solidity")]
+    compiler: Compiler,
+
+    /// Block to call at: number, hash or tag
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+}
+
+pub async fn run(args: CallArgs, rpc_url: &str) -> Result<()> {
+    let sig: Signature = args.sig.parse()?;
+    let calldata = format!("0x{}", hex::encode(sig.encode_call(&args.args)?));
+
+    let mut overrides = StateOverride::default();
+    println!("--- SIMULATED CALL ---");
+    for spec in &args.overrides {
+        let address = spec.address.as_deref().unwrap_or(&args.contract);
+        let slot = derive_slot_path_for(spec.position, &spec.keys, args.compiler);
+        overrides.set_storage(address, slot, spec.value.0);
+        println!("Override: {} slot {} = {}", address, slot, spec.value);
+    }
+
+    let raw =
+        eth_call_with_overrides(rpc_url, &args.contract, &calldata, args.block, &overrides).await?;
+    println!("Raw return: {}", raw);
+
+    let data = hex::decode(raw.trim_start_matches("0x")).context("Invalid return data")?;
+    for (ty, value) in sig.outputs.iter().zip(sig.decode_output(&data)?) {
+        println!("{}: {}", ty, value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_override_spec() -> Result<()> {
+        let spec: OverrideSpec =
+            "0xdAC17F958D2ee523a2206206994597C13D831ec7@2[0xF977814e90dA44bFA03b6295A0616a897441aceC]=1000000"
+                .parse()?;
+        assert_eq!(
+            spec.address.as_deref(),
+            Some("0xdAC17F958D2ee523a2206206994597C13D831ec7")
+        );
+        assert_eq!(spec.position, Slot::from(2));
+        assert_eq!(spec.keys.len(), 1);
+        assert_eq!(spec.value, Slot::from(1_000_000));
+
+        let plain: OverrideSpec = "7=0x01".parse()?;
+        assert!(plain.address.is_none() && plain.keys.is_empty());
+        assert!("7[0xabc".parse::<OverrideSpec>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_negative_arguments_are_values() -> Result<()> {
+        use clap::Parser;
+
+        let cli = crate::Cli::try_parse_from([
+            "rpc_surgeon",
+            "call",
+            "-c",
+            "0xabc",
+            "--sig",
+            "f(int256)",
+            "-5",
+        ])?;
+        let Some(crate::Command::Call(args)) = cli.command else {
+            anyhow::bail!("expected the call subcommand");
+        };
+        assert_eq!(args.args, vec!["-5"]);
+        Ok(())
+    }
+}
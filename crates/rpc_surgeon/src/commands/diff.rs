@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use rpc_surgeon::{
+    BatchOptions, BlockId, BytesLayout, DecodedValue, ResolvedVariable, Slot, SlotDiff,
+    StorageLayout, ValueType, VariableKind, decode_field, decode_string, diff_slots, diff_storage,
+    resolve_block,
+};
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Block before the change
+    #[arg(long)]
+    from: BlockId,
+
+    /// Block after the change
+    #[arg(long, default_value = "latest")]
+    to: BlockId,
+
+    /// Slots to compare (repeat). Without any, every slot is compared via debug_storageRangeAt
+    #[arg(short, long = "slot")]
+    slots: Vec<Slot>,
+
+    /// Compiler storageLayout JSON used to name and decode changed slots
+    #[arg(long)]
+    layout: Option<PathBuf>,
+
+    /// Slots per debug_storageRangeAt page
+    #[arg(long, default_value_t = 1024)]
+    page_size: usize,
+}
+
+pub async fn run(args: DiffArgs, rpc_url: &str) -> Result<()> {
+    let layout = args
+        .layout
+        .as_deref()
+        .map(StorageLayout::load)
+        .transpose()?;
+    // Both ends are pinned once, the header then names the blocks actually compared
+    let from = resolve_block(rpc_url, args.from).await?;
+    let to = resolve_block(rpc_url, args.to).await?;
+
+    let diffs = if args.slots.is_empty() {
+        let (BlockId::Number(from), BlockId::Number(to)) = (from, to) else {
+            anyhow::bail!("Full storage diffs need --from and --to as numbers or tags, not hashes");
+        };
+        diff_storage(rpc_url, &args.contract, from, to, args.page_size)
+            .await
+            .context("debug_storageRangeAt failed, pass --slot to compare specific slots")?
+    } else {
+        diff_slots(
+            rpc_url,
+            &args.contract,
+            &args.slots,
+            from,
+            to,
+            BatchOptions::default(),
+        )
+        .await?
+    };
+
+    println!("--- STORAGE DIFF {} -> {} ---", from, to);
+    for diff in &diffs {
+        print_diff(diff, layout.as_ref())?;
+    }
+    println!("{} slot(s) changed", diffs.len());
+    Ok(())
+}
+
+fn print_diff(diff: &SlotDiff, layout: Option<&StorageLayout>) -> Result<()> {
+    match diff.slot {
+        Some(slot) => println!("Slot {}", slot),
+        None => println!("Hashed key {} (no preimage)", diff.hashed_key),
+    }
+    println!(
+        "  0x{} -> 0x{}",
+        hex::encode(diff.before),
+        hex::encode(diff.after)
+    );
+
+    let (Some(layout), Some(slot)) = (layout, diff.slot) else {
+        return Ok(());
+    };
+    for (path, variable) in layout.variables_at(slot)? {
+        match (
+            decode(&variable, &diff.before),
+            decode(&variable, &diff.after),
+        ) {
+            (Some(before), Some(after)) if before != after => {
+                println!("  {}: {} -> {}", path, before, after)
+            }
+            (Some(_), Some(_)) => {}
+            _ => println!("  {} ({})", path, variable.label),
+        }
+    }
+    Ok(())
+}
+
+// Values that fit in the word itself, long strings and containers only get a label
+fn decode(variable: &ResolvedVariable, word: &[u8; 32]) -> Option<DecodedValue> {
+    match &variable.kind {
+        VariableKind::Value(ty) => decode_field(word, variable.offset, None, *ty).ok(),
+        VariableKind::DynamicArray => decode_field(word, 0, None, ValueType::Uint(256)).ok(),
+        VariableKind::String | VariableKind::Bytes => match BytesLayout::from_word(word).ok()? {
+            BytesLayout::Short(data) if variable.kind == VariableKind::String => {
+                Some(decode_string(data))
+            }
+            BytesLayout::Short(data) => Some(DecodedValue::Bytes(data)),
+            BytesLayout::Long(_) => None,
+        },
+        _ => None,
+    }
+}
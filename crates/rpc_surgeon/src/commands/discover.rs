@@ -0,0 +1,60 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{BlockId, DecodedValue, discover_balance_slot};
+
+#[derive(Args, Debug)]
+pub struct DiscoverArgs {
+    /// ERC-20 token address
+    #[arg(short, long)]
+    contract: String,
+
+    /// An address known to hold a non-zero balance
+    #[arg(short = 'o', long)]
+    holder: String,
+
+    /// Try mapping positions 0..max
+    #[arg(long, default_value_t = 20)]
+    max_slot: u64,
+
+    /// Only check slots read by balanceOf (eth_createAccessList)
+    #[arg(short, long)]
+    access_list: bool,
+
+    /// Block to read at: number, hash or tag
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+}
+
+pub async fn run(args: DiscoverArgs, rpc_url: &str) -> Result<()> {
+    let discovery = discover_balance_slot(
+        rpc_url,
+        &args.contract,
+        &args.holder,
+        args.max_slot,
+        args.access_list,
+        args.block,
+    )
+    .await?;
+
+    println!("--- BALANCE SLOT DISCOVERY ---");
+    println!(
+        "balanceOf: {}",
+        DecodedValue::Uint(discovery.balance.to_vec())
+    );
+    for found in &discovery.matches {
+        println!(
+            "Match: slot {} ({} ordering) -> {}",
+            found.position, found.compiler, found.slot
+        );
+    }
+    for slot in &discovery.unexplained {
+        println!(
+            "Read by balanceOf with a matching value (unknown layout): {}",
+            slot
+        );
+    }
+    if discovery.matches.is_empty() && discovery.unexplained.is_empty() {
+        println!("No match in slots 0..{}", args.max_slot);
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use rpc_surgeon::{
+    DumpEntry, MappingKey, PreimageCandidates, Slot, SlotOrigin, StorageLayout, annotate_dump,
+    parse_holders, storage_dump,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct DumpArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Dump the storage as of the end of this block
+    #[arg(short, long)]
+    block: u64,
+
+    /// Candidate mapping keys to reverse hashed slots with (repeat), `type:value` or an address
+    #[arg(short, long = "key")]
+    keys: Vec<MappingKey>,
+
+    /// File of candidate addresses (newline list, CSV or JSON, as for `balances`)
+    #[arg(long)]
+    keys_file: Option<PathBuf>,
+
+    /// Also try uint256 keys 0..n
+    #[arg(long, default_value_t = 256)]
+    max_int: u64,
+
+    /// Declared positions 0..n tried as mapping and array heads
+    #[arg(long, default_value_t = 64)]
+    max_position: u64,
+
+    /// Array words 0..n tried after each array head
+    #[arg(long, default_value_t = 1024)]
+    max_index: u64,
+
+    /// Compiler storageLayout JSON used to name positions
+    #[arg(long)]
+    layout: Option<PathBuf>,
+
+    /// Slots per debug_storageRangeAt page
+    #[arg(long, default_value_t = 1024)]
+    page_size: usize,
+
+    /// Write the JSON dump to a file instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub async fn run(args: DumpArgs, rpc_url: &str) -> Result<()> {
+    let mut keys = args.keys.clone();
+    keys.push(args.contract.parse()?);
+    if let Some(path) = &args.keys_file {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        keys.extend(parse_holders(&text)?.into_iter().map(MappingKey::Address));
+    }
+    let candidates = PreimageCandidates {
+        keys,
+        key_pairs: Vec::new(),
+        max_position: args.max_position,
+        max_index: args.max_index,
+    }
+    .with_small_ints(args.max_int);
+
+    let names = match &args.layout {
+        Some(path) => position_names(&StorageLayout::load(path)?)?,
+        None => HashMap::new(),
+    };
+
+    let entries = storage_dump(rpc_url, &args.contract, args.block, args.page_size).await?;
+    let dump = annotate_dump(&entries, &candidates);
+    let resolved = dump
+        .iter()
+        .filter(|entry| entry.origin != SlotOrigin::Unknown)
+        .count();
+
+    let report = serde_json::json!({
+        "contract": args.contract,
+        "block": args.block,
+        "slots": dump.iter().map(|entry| to_json(entry, &names)).collect::<Vec<_>>(),
+    });
+    let report = serde_json::to_string_pretty(&report)? + "\n";
+
+    match &args.out {
+        Some(path) => std::fs::write(path, report)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", report),
+    }
+    eprintln!(
+        "{} slots dumped at block {}, {} annotated",
+        dump.len(),
+        args.block,
+        resolved
+    );
+    Ok(())
+}
+
+// Declared position -> variable names sharing it (packed variables share a slot)
+fn position_names(layout: &StorageLayout) -> Result<HashMap<u64, String>> {
+    let mut names: HashMap<u64, String> = HashMap::new();
+    for entry in &layout.storage {
+        let Some(position) = entry.slot.parse::<Slot>()?.to_u64() else {
+            continue;
+        };
+        names
+            .entry(position)
+            .and_modify(|name| {
+                name.push_str(", ");
+                name.push_str(&entry.label);
+            })
+            .or_insert_with(|| entry.label.clone());
+    }
+    Ok(names)
+}
+
+fn to_json(entry: &DumpEntry, names: &HashMap<u64, String>) -> serde_json::Value {
+    let (variable, origin) = match &entry.origin {
+        SlotOrigin::Direct(position) => (
+            names.get(position),
+            serde_json::json!({ "kind": "direct", "position": position }),
+        ),
+        SlotOrigin::Mapping {
+            position,
+            key,
+            compiler,
+        } => (
+            names.get(position),
+            serde_json::json!({
+                "kind": "mapping",
+                "position": position,
+                "key": key.to_string(),
+                "compiler": compiler.to_string(),
+            }),
+        ),
+        SlotOrigin::NestedMapping {
+            position,
+            keys,
+            compiler,
+        } => (
+            names.get(position),
+            serde_json::json!({
+                "kind": "nestedMapping",
+                "position": position,
+                "keys": keys.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "compiler": compiler.to_string(),
+            }),
+        ),
+        SlotOrigin::ArrayWord { position, word } => (
+            names.get(position),
+            serde_json::json!({ "kind": "array", "position": position, "word": word }),
+        ),
+        SlotOrigin::Unknown => (None, serde_json::json!({ "kind": "unknown" })),
+    };
+
+    serde_json::json!({
+        "slot": entry.slot.map(|slot| slot.to_string()),
+        "hashedKey": entry.hashed_key.to_string(),
+        "value": format!("0x{}", hex::encode(entry.value)),
+        "variable": variable,
+        "origin": origin,
+    })
+}
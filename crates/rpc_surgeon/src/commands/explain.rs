@@ -0,0 +1,91 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{
+    DecodedValue, MappingKey, PreimageCandidates, RangeEntry, SlotOrigin, annotate_dump,
+    trace_storage_changes, transaction_addresses,
+};
+
+#[derive(Args, Debug)]
+pub struct ExplainArgs {
+    /// Transaction hash
+    tx: String,
+
+    /// Extra mapping keys to recognize slots with (repeat), `type:value` or an address
+    #[arg(short, long = "key")]
+    keys: Vec<MappingKey>,
+
+    /// Declared positions 0..n tried as mapping heads
+    #[arg(long, default_value_t = 64)]
+    max_position: u64,
+}
+
+pub async fn run(args: ExplainArgs, rpc_url: &str) -> Result<()> {
+    let changes = trace_storage_changes(rpc_url, &args.tx).await?;
+    let addresses = transaction_addresses(rpc_url, &args.tx).await?;
+
+    // Sender, recipient, log addresses and every touched contract are the likely keys
+    let mut accounts: Vec<MappingKey> = addresses.into_iter().map(MappingKey::Address).collect();
+    for account in &changes {
+        let key = account.address.parse()?;
+        if !accounts.contains(&key) {
+            accounts.push(key);
+        }
+    }
+    // and pairs of them the likely allowance[owner][spender] keys
+    let key_pairs = accounts
+        .iter()
+        .flat_map(|outer| {
+            accounts
+                .iter()
+                .filter(move |inner| *inner != outer)
+                .map(move |inner| (outer.clone(), inner.clone()))
+        })
+        .collect();
+    let mut keys = args.keys.clone();
+    keys.extend(accounts);
+    let candidates = PreimageCandidates {
+        keys,
+        key_pairs,
+        max_position: args.max_position,
+        ..PreimageCandidates::default()
+    };
+
+    println!("--- STORAGE CHANGES {} ---", args.tx);
+    for account in &changes {
+        println!(
+            "Contract {} ({} slots)",
+            account.address,
+            account.slots.len()
+        );
+
+        let entries: Vec<RangeEntry> = account
+            .slots
+            .iter()
+            .map(|diff| RangeEntry {
+                hashed_key: diff.hashed_key,
+                key: diff.slot,
+                value: diff.after,
+            })
+            .collect();
+        let annotated = annotate_dump(&entries, &candidates);
+
+        for (diff, entry) in account.slots.iter().zip(&annotated) {
+            let slot = diff
+                .slot
+                .map_or_else(|| "?".to_string(), |slot| slot.to_string());
+            println!("  {}", slot);
+            if entry.origin != SlotOrigin::Unknown {
+                println!("    Origin: {}", entry.origin);
+            }
+            println!(
+                "    {} -> {}",
+                DecodedValue::Uint(diff.before.to_vec()),
+                DecodedValue::Uint(diff.after.to_vec())
+            );
+        }
+    }
+    if changes.is_empty() {
+        println!("No storage changed");
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{
+    BatchOptions, BlockId, MappingKey, SlotPattern, StorageRequest, analyze_bytecode,
+    derive_slot_path_for, get_code, get_storage_batch,
+};
+
+#[derive(Args, Debug)]
+pub struct InferArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Mapping keys applied to every mapping candidate, e.g. `-k <HOLDER>` to find a balance
+    #[arg(short, long = "key")]
+    keys: Vec<MappingKey>,
+
+    /// Block to read code and values at: number, hash or tag
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+
+    /// Only list the n most accessed candidates
+    #[arg(long, default_value_t = 20)]
+    top: usize,
+}
+
+pub async fn run(args: InferArgs, rpc_url: &str) -> Result<()> {
+    let code = get_code(rpc_url, &args.contract, args.block).await?;
+    anyhow::ensure!(!code.is_empty(), "No code at {}", args.contract);
+
+    let mut candidates = analyze_bytecode(&code);
+    candidates.truncate(args.top);
+
+    // Plain variables read as-is, mappings at the slot derived from --key
+    let slots: Vec<_> = candidates
+        .iter()
+        .map(|candidate| match candidate.pattern {
+            SlotPattern::Variable(slot) => Some(slot),
+            SlotPattern::Mapping { position, compiler } if !args.keys.is_empty() => {
+                Some(derive_slot_path_for(position, &args.keys, compiler))
+            }
+            _ => None,
+        })
+        .collect();
+    let requests: Vec<StorageRequest> = slots
+        .iter()
+        .flatten()
+        .map(|slot| StorageRequest {
+            address: args.contract.clone(),
+            slot: *slot,
+            block: args.block,
+        })
+        .collect();
+    let mut values = get_storage_batch(rpc_url, &requests, BatchOptions::default())
+        .await
+        .into_iter();
+
+    println!(
+        "--- STORAGE LAYOUT GUESS ({} bytes of code) ---",
+        code.len()
+    );
+    for (rank, (candidate, slot)) in candidates.iter().zip(&slots).enumerate() {
+        let selectors: Vec<String> = candidate
+            .selectors
+            .iter()
+            .map(|selector| format!("0x{}", hex::encode(selector)))
+            .collect();
+        println!(
+            "#{} {} ({} reads, {} writes) selectors: {}",
+            rank + 1,
+            candidate.pattern,
+            candidate.reads,
+            candidate.writes,
+            if selectors.is_empty() {
+                "-".to_string()
+            } else {
+                selectors.join(", ")
+            }
+        );
+
+        let Some(slot) = slot else {
+            continue;
+        };
+        let Some(value) = values.next() else {
+            continue;
+        };
+        if !matches!(candidate.pattern, SlotPattern::Variable(_)) {
+            println!("   Derived slot: {}", slot);
+        }
+        match value {
+            Ok(raw) => println!("   Value: {}", raw),
+            Err(e) => println!("   Value: error: {:#}", e),
+        }
+    }
+    Ok(())
+}
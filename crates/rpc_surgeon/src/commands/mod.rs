@@ -0,0 +1,13 @@
+pub mod balances;
+pub mod call;
+pub mod diff;
+pub mod discover;
+pub mod dump;
+pub mod explain;
+pub mod infer;
+pub mod namespace;
+pub mod proxy;
+pub mod read;
+pub mod shell;
+pub mod watch;
+pub mod write;
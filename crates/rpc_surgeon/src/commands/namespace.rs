@@ -0,0 +1,33 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{Compiler, MappingKey, Namespace, derive_namespaced_slot};
+
+#[derive(Args, Debug)]
+pub struct NamespaceArgs {
+    /// Namespace id: `erc7201:<id>` (default for a bare id) or `eip1967:<id>`
+    namespace: Namespace,
+
+    /// Slot of the member within the namespaced struct
+    #[arg(short, long, default_value_t = 0)]
+    slot: u64,
+
+    /// Mapping keys applied to the member, outermost first (repeat for nested mappings)
+    #[arg(short, long = "key")]
+    keys: Vec<MappingKey>,
+
+    /// Storage convention used to derive the slot (solidity, vyper)
+    #[arg(long, default_value = "solidity")]
+    compiler: Compiler,
+}
+
+pub fn run(args: NamespaceArgs) -> Result<()> {
+    let root = args.namespace.root();
+
+    println!("--- NAMESPACE {} ---", args.namespace);
+    println!("Root: {}", root);
+    if args.slot > 0 || !args.keys.is_empty() {
+        let target = derive_namespaced_slot(&args.namespace, args.slot, &args.keys, args.compiler);
+        println!("Target Slot: {}", target);
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{BlockId, detect_proxy};
+
+#[derive(Args, Debug)]
+pub struct ProxyArgs {
+    /// Proxy contract address
+    #[arg(short, long)]
+    contract: String,
+
+    /// Block to read at: number, hash or tag
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+}
+
+pub async fn run(args: ProxyArgs, rpc_url: &str) -> Result<()> {
+    let info = detect_proxy(rpc_url, &args.contract, args.block).await?;
+    let show = |addr: Option<[u8; 20]>| match addr {
+        Some(addr) => format!("0x{}", hex::encode(addr)),
+        None => "-".to_string(),
+    };
+
+    println!("--- PROXY INSPECTION ---");
+    println!("Kind: {}", info.kind);
+    println!("Implementation: {}", show(info.implementation));
+    println!("Admin: {}", show(info.admin));
+    if info.beacon.is_some() {
+        println!("Beacon: {}", show(info.beacon));
+    }
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use rpc_surgeon::{
+    BlockId, Compiler, DecodedValue, MappingKey, Namespace, Slot, StorageLayout, ValueType,
+    decode_field, decode_string, derive_slot_path_for, get_state_root, get_storage_at,
+    get_verified_storage, parse_word, quorum_storage_at, read_bytes_at_for, resolve_block,
+    storage_history,
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// How to interpret the target slot
+#[derive(Debug, Clone)]
+enum ReadAs {
+    Value(ValueType),
+    /// Dynamic `string` / `bytes`: head slot plus continuation slots
+    String,
+    Bytes,
+}
+
+impl FromStr for ReadAs {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "string" => Ok(ReadAs::String),
+            "bytes" => Ok(ReadAs::Bytes),
+            _ => s.parse().map(ReadAs::Value),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ReadArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Mapping keys, outermost first (repeat for nested mappings).
+    /// Bare values are addresses, otherwise `type:value` (uint256:42, bytes32:0x.., string:USDC)
+    #[arg(
+        short = 'k',
+        long = "key",
+        visible_alias = "owner",
+        visible_short_alias = 'o'
+    )]
+    keys: Vec<MappingKey>,
+
+    /// Declared slot of the variable, decimal or 0x-prefixed 256-bit hex
+    #[arg(short, long, required_unless_present = "layout")]
+    slot: Option<Slot>,
+
+    /// Namespaced storage struct (`erc7201:<id>`, `eip1967:<id>`), --slot is then the
+    /// member's slot within the struct, e.g. `openzeppelin.storage.ERC20 -s 0` for _balances
+    #[arg(long, conflicts_with = "layout")]
+    namespace: Option<Namespace>,
+
+    /// Dynamic array index applied after the keys (repeat for nested arrays)
+    #[arg(short, long = "index")]
+    indices: Vec<u64>,
+
+    /// Element size in bytes for the last index (20 for address[], 96 for a 3-word struct)
+    #[arg(short, long, default_value_t = 32)]
+    element_size: u64,
+
+    /// Struct member word offset, applied last
+    #[arg(short, long)]
+    member: Option<u64>,
+
+    /// Read the length word of the dynamic array at the derived slot (keys and member
+    /// applied, no index)
+    #[arg(short, long, conflicts_with_all = ["indices", "value_type", "offset", "size"])]
+    length: bool,
+
+    /// Type of the value to decode (uint112, int24, address, bool, bytes4, string, bytes, ...)
+    #[arg(short = 't', long = "type", default_value = "uint256")]
+    value_type: ReadAs,
+
+    /// Byte offset of the field from the low-order end of the word (packed slots)
+    #[arg(long)]
+    offset: Option<usize>,
+
+    /// Field width in bytes, defaults to the width of --type
+    #[arg(long)]
+    size: Option<usize>,
+
+    /// Storage convention for keys, arrays and strings (solidity, vyper)
+    #[arg(long, default_value = "solidity", conflicts_with = "layout")]
+    compiler: Compiler,
+
+    /// Compiler storageLayout JSON (`solc --storage-layout`, `forge inspect <C> storageLayout`)
+    #[arg(long, requires = "path", conflicts_with_all = ["slot", "keys", "indices", "member"])]
+    layout: Option<PathBuf>,
+
+    /// Variable path resolved against --layout, e.g. `balanceOf[0xabc]` or `positions[7].liquidity`
+    #[arg(short, long, requires = "layout")]
+    path: Option<String>,
+
+    /// Block to read at: number, hash or tag (latest, earliest, safe, finalized, pending)
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+
+    /// Read the slot over --block..=--to-block and print each change
+    #[arg(long)]
+    to_block: Option<u64>,
+
+    /// Block interval between reads in range mode
+    #[arg(long, default_value_t = 1, requires = "to_block")]
+    step: u64,
+
+    /// Verify the value with eth_getProof against a state root before reporting it
+    /// (single-word reads at one block only)
+    #[arg(long, conflicts_with_all = ["layout", "to_block"])]
+    prove: bool,
+
+    /// Trusted state root for --prove, otherwise taken from the RPC's block header
+    #[arg(long, requires = "prove")]
+    state_root: Option<Slot>,
+
+    /// Extra RPC endpoint to cross-check the value against (repeat), read at one pinned block
+    /// (single-word reads at one block only)
+    #[arg(long = "provider", conflicts_with_all = ["prove", "layout", "to_block"])]
+    providers: Vec<String>,
+
+    /// Providers (including --rpc) that must agree, defaults to a majority
+    #[arg(long, requires = "providers")]
+    quorum: Option<usize>,
+}
+
+pub async fn run(args: ReadArgs, rpc_url: &str) -> Result<()> {
+    let contract = args.contract.as_str();
+    // The continuation slots of strings are not proven, refuse rather than print unverified data
+    anyhow::ensure!(
+        !args.prove || matches!(args.value_type, ReadAs::Value(_)),
+        "--prove only verifies single-word types, not -t string / bytes"
+    );
+    // Same for the quorum, which compares one word per provider
+    anyhow::ensure!(
+        args.providers.is_empty() || matches!(args.value_type, ReadAs::Value(_)),
+        "--provider only cross-checks single-word types, not -t string / bytes"
+    );
+
+    if let (Some(layout), Some(path)) = (&args.layout, &args.path) {
+        let variable = StorageLayout::load(layout)?.resolve(path)?;
+
+        println!("--- SURGERY RESULT ---");
+        println!("Variable: {} ({})", path, variable.label);
+        println!("Target Slot: {}", variable.slot);
+        if variable.offset > 0 {
+            println!("Byte offset: {}", variable.offset);
+        }
+        let value = variable.read(rpc_url, contract, args.block).await?;
+        println!("Decoded value: {}", value);
+        return Ok(());
+    }
+
+    let mut base_slot = args.slot.context("--slot is required without --layout")?;
+    if let Some(namespace) = &args.namespace {
+        base_slot = namespace.root().wrapping_add(&base_slot);
+    }
+    let mut target_slot = derive_slot_path_for(base_slot, &args.keys, args.compiler);
+    let mut byte_offset = 0;
+    for (i, index) in args.indices.iter().enumerate() {
+        // Outer dimensions of nested arrays are themselves array heads (one word)
+        let size = if i + 1 == args.indices.len() {
+            args.element_size
+        } else {
+            32
+        };
+        (target_slot, byte_offset) = target_slot.array_element_for(*index, size, args.compiler)?;
+    }
+    if let Some(member) = args.member {
+        target_slot = target_slot.offset(member);
+    }
+
+    println!("--- SURGERY RESULT ---");
+    if let Some(namespace) = &args.namespace {
+        println!("Namespace root: {} ({})", namespace.root(), namespace);
+    }
+    println!("Target Slot: {}", target_slot);
+    if byte_offset > 0 {
+        println!("Byte offset: {}", byte_offset);
+    }
+
+    let value_type = match args.value_type {
+        ReadAs::Value(value_type) => value_type,
+        dynamic => {
+            let data = read_bytes_at_for(rpc_url, contract, target_slot, args.block, args.compiler)
+                .await?;
+            println!("Length: {} bytes", data.len());
+            let value = match dynamic {
+                ReadAs::String => decode_string(data),
+                _ => DecodedValue::Bytes(data),
+            };
+            println!("Decoded value: {}", value);
+            return Ok(());
+        }
+    };
+
+    let offset = args.offset.unwrap_or(byte_offset);
+
+    if let Some(to_block) = args.to_block {
+        let BlockId::Number(from_block) = args.block else {
+            anyhow::bail!("--to-block needs a numeric --block to start from");
+        };
+        let changes = storage_history(
+            rpc_url,
+            contract,
+            target_slot,
+            from_block,
+            to_block,
+            args.step,
+        )
+        .await?;
+
+        let mut previous: Option<DecodedValue> = None;
+        for change in changes {
+            let value = decode_field(&parse_word(&change.raw)?, offset, args.size, value_type)?;
+            match &previous {
+                Some(old) => println!("Block {}: {} -> {}", change.block, old, value),
+                None => println!("Block {}: {}", change.block, value),
+            }
+            previous = Some(value);
+        }
+        return Ok(());
+    }
+
+    let word = if args.prove {
+        // The root and the proof must come from the same block, even if the head moves
+        let block = resolve_block(rpc_url, args.block).await?;
+        let state_root = match args.state_root {
+            Some(root) => *root.as_bytes(),
+            None => {
+                println!("Warning: state root taken from the same RPC");
+                get_state_root(rpc_url, block).await?
+            }
+        };
+        let verified =
+            get_verified_storage(rpc_url, contract, target_slot, block, &state_root).await?;
+        println!(
+            "Proof: verified against state root 0x{} at block {}",
+            hex::encode(state_root),
+            block
+        );
+        verified.value
+    } else if !args.providers.is_empty() {
+        let mut providers = vec![rpc_url.to_string()];
+        providers.extend(args.providers.iter().cloned());
+        let quorum = args.quorum.unwrap_or(providers.len() / 2 + 1);
+
+        let read = quorum_storage_at(&providers, contract, target_slot, args.block, quorum).await?;
+        println!(
+            "Quorum: {} of {} providers agree at block {}",
+            read.agreeing.len(),
+            providers.len(),
+            read.block
+        );
+        for (provider, answer) in &read.diverging {
+            println!("Diverged: {} -> {}", provider, answer);
+        }
+        read.value
+    } else {
+        let raw_word =
+            get_storage_at(rpc_url, contract, &target_slot.to_string(), args.block).await?;
+        parse_word(&raw_word)?
+    };
+    println!("Raw value: 0x{}", hex::encode(word));
+
+    if args.length {
+        // The head word is the whole length, never a packed field
+        println!("Array length: {}", DecodedValue::Uint(word.to_vec()));
+        return Ok(());
+    }
+    let value = decode_field(&word, offset, args.size, value_type)?;
+    println!("Decoded value: {}", value);
+    Ok(())
+}
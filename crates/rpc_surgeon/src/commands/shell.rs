@@ -0,0 +1,454 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use rpc_surgeon::{
+    BlockId, Compiler, DecodedValue, MappingKey, Slot, ValueType, decode_field,
+    derive_slot_path_for, get_storage_at, parse_word,
+};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::{DefaultHistory, History, SearchDirection};
+use rustyline::validate::Validator;
+use rustyline::{Config, Editor, Helper};
+use std::path::PathBuf;
+
+const COMMANDS: &[&str] = &[
+    "contract", "block", "compiler", "slot", "map", "decode", "history", "help", "exit",
+];
+
+const HELP: &[(&str, &str)] = &[
+    ("contract <ADDRESS>", "set the contract to read"),
+    ("block <BLOCK>", "set the block: number, hash or tag"),
+    (
+        "compiler <NAME>",
+        "set the key hashing order: solidity, vyper",
+    ),
+    ("slot <POSITION>", "read a raw slot"),
+    (
+        "map <POSITION> <KEY>...",
+        "read mapping[key]..., outermost key first",
+    ),
+    (
+        "decode <TYPE> [@OFFSET]",
+        "decode the last word, offset in bytes from the low end",
+    ),
+    ("history, !!, !N", "list, re-run the last or the N-th line"),
+    ("<PREFIX><TAB>", "complete the command name"),
+    ("exit", "leave the shell"),
+];
+
+const HISTORY_FILE: &str = ".rpc_surgeon_history";
+const HISTORY_LIMIT: usize = 1000;
+
+#[derive(Args, Debug)]
+pub struct ShellArgs {
+    /// Contract to start with
+    #[arg(short, long)]
+    contract: Option<String>,
+
+    /// Block to start with: number, hash or tag
+    #[arg(short, long, default_value = "latest")]
+    block: BlockId,
+
+    /// Storage convention to start with (solidity, vyper)
+    #[arg(long, default_value = "solidity")]
+    compiler: Compiler,
+}
+
+/// One parsed shell line
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Contract(String),
+    Block(BlockId),
+    Compiler(Compiler),
+    Slot(Slot),
+    Map(Slot, Vec<MappingKey>),
+    Decode(ValueType, usize),
+    History,
+    Help,
+    Exit,
+}
+
+// Commands starting with `prefix`
+fn completions(prefix: &str) -> Vec<&'static str> {
+    COMMANDS
+        .iter()
+        .copied()
+        .filter(|command| command.starts_with(prefix))
+        .collect()
+}
+
+// Exact command name or an unambiguous prefix of one
+fn resolve(word: &str) -> Result<&'static str> {
+    if let Some(command) = COMMANDS.iter().find(|command| **command == word) {
+        return Ok(command);
+    }
+    match completions(word).as_slice() {
+        [command] => Ok(command),
+        [] => anyhow::bail!("Unknown command: {} (try help)", word),
+        many => anyhow::bail!("Ambiguous command {}: {}", word, many.join(", ")),
+    }
+}
+
+/// Line editor helper: Tab completes the command name, the first word of the line
+struct CommandCompleter;
+
+impl Completer for CommandCompleter {
+    type Candidate = &'static str;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<&'static str>)> {
+        let typed = &line[..pos];
+        let start = typed.len() - typed.trim_start().len();
+        let word = &typed[start..];
+        if word.contains(char::is_whitespace) {
+            return Ok((pos, Vec::new()));
+        }
+        Ok((start, completions(word)))
+    }
+}
+
+impl Hinter for CommandCompleter {
+    type Hint = String;
+}
+impl Highlighter for CommandCompleter {}
+impl Validator for CommandCompleter {}
+impl Helper for CommandCompleter {}
+
+fn parse_line(line: &str) -> Result<Option<Line>> {
+    let mut words = line.split_whitespace();
+    let Some(first) = words.next() else {
+        return Ok(None);
+    };
+    let command = resolve(first)?;
+    let args: Vec<&str> = words.collect();
+
+    let arity = |min: usize, max: usize| {
+        anyhow::ensure!(
+            (min..=max).contains(&args.len()),
+            "Usage: {}",
+            HELP.iter()
+                .find(|(usage, _)| usage.starts_with(command))
+                .map_or(command, |(usage, _)| usage)
+        );
+        Ok(())
+    };
+
+    let line = match command {
+        "contract" => {
+            arity(1, 1)?;
+            Line::Contract(args[0].to_string())
+        }
+        "block" => {
+            arity(1, 1)?;
+            Line::Block(args[0].parse()?)
+        }
+        "compiler" => {
+            arity(1, 1)?;
+            Line::Compiler(args[0].parse()?)
+        }
+        "slot" => {
+            arity(1, 1)?;
+            Line::Slot(args[0].parse()?)
+        }
+        "map" => {
+            arity(2, usize::MAX)?;
+            let keys = args[1..]
+                .iter()
+                .map(|key| key.parse())
+                .collect::<Result<_>>()?;
+            Line::Map(args[0].parse()?, keys)
+        }
+        "decode" => {
+            arity(1, 2)?;
+            let offset = match args.get(1) {
+                Some(offset) => offset
+                    .trim_start_matches('@')
+                    .parse()
+                    .with_context(|| format!("Invalid offset: {}", offset))?,
+                None => 0,
+            };
+            Line::Decode(args[0].parse()?, offset)
+        }
+        "history" => Line::History,
+        "help" => Line::Help,
+        _ => Line::Exit,
+    };
+    Ok(Some(line))
+}
+
+// `!!` is the last line, `!N` the N-th (1-based, as `history` numbers them)
+fn expand_history(line: &str, history: &[String]) -> Result<String> {
+    let Some(reference) = line.strip_prefix('!') else {
+        return Ok(line.to_string());
+    };
+    let entry = if reference == "!" {
+        history.last()
+    } else {
+        let n: usize = reference
+            .parse()
+            .with_context(|| format!("Invalid history reference: {}", line))?;
+        n.checked_sub(1).and_then(|i| history.get(i))
+    };
+    entry
+        .cloned()
+        .with_context(|| format!("No history entry {}", line))
+}
+
+/// Two-column bordered table
+fn print_table(rows: &[(&str, String)]) {
+    let key_width = rows
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or_default();
+    let value_width = rows
+        .iter()
+        .map(|(_, value)| value.len())
+        .max()
+        .unwrap_or_default();
+    let border = format!(
+        "+-{}-+-{}-+",
+        "-".repeat(key_width),
+        "-".repeat(value_width)
+    );
+
+    println!("{}", border);
+    for (key, value) in rows {
+        println!("| {:<key_width$} | {:<value_width$} |", key, value);
+    }
+    println!("{}", border);
+}
+
+struct Session {
+    rpc_url: String,
+    contract: Option<String>,
+    block: BlockId,
+    compiler: Compiler,
+    /// Last word read, the input of `decode`
+    last_word: Option<[u8; 32]>,
+}
+
+impl Session {
+    fn prompt(&self) -> String {
+        let contract = self.contract.as_deref().unwrap_or("no contract");
+        format!("surgeon({}@{})> ", contract, self.block)
+    }
+
+    fn print_context(&self) {
+        print_table(&[
+            (
+                "contract",
+                self.contract.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            ("block", self.block.to_string()),
+            ("compiler", self.compiler.to_string()),
+        ]);
+    }
+
+    async fn read(&mut self, slot: Slot) -> Result<[u8; 32]> {
+        let contract = self
+            .contract
+            .as_deref()
+            .context("No contract set, use `contract <ADDRESS>`")?;
+        let raw = get_storage_at(&self.rpc_url, contract, &slot.to_string(), self.block).await?;
+        let word = parse_word(&raw)?;
+        self.last_word = Some(word);
+        Ok(word)
+    }
+
+    async fn execute(&mut self, line: Line, history: &[String]) -> Result<()> {
+        match line {
+            Line::Contract(contract) => {
+                self.contract = Some(contract);
+                self.print_context();
+            }
+            Line::Block(block) => {
+                self.block = block;
+                self.print_context();
+            }
+            Line::Compiler(compiler) => {
+                self.compiler = compiler;
+                self.print_context();
+            }
+            Line::Slot(slot) => {
+                let word = self.read(slot).await?;
+                print_table(&[
+                    ("slot", slot.to_string()),
+                    ("raw", format!("0x{}", hex::encode(word))),
+                    ("uint256", DecodedValue::Uint(word.to_vec()).to_string()),
+                ]);
+            }
+            Line::Map(position, keys) => {
+                let slot = derive_slot_path_for(position, &keys, self.compiler);
+                let word = self.read(slot).await?;
+                let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+                print_table(&[
+                    ("position", position.to_string()),
+                    ("keys", keys.join(", ")),
+                    ("slot", slot.to_string()),
+                    ("raw", format!("0x{}", hex::encode(word))),
+                    ("uint256", DecodedValue::Uint(word.to_vec()).to_string()),
+                ]);
+            }
+            Line::Decode(value_type, offset) => {
+                let word = self
+                    .last_word
+                    .context("Nothing read yet, use `slot` or `map` first")?;
+                let value = decode_field(&word, offset, None, value_type)?;
+                print_table(&[
+                    ("raw", format!("0x{}", hex::encode(word))),
+                    ("offset", offset.to_string()),
+                    ("value", value.to_string()),
+                ]);
+            }
+            Line::History => {
+                for (i, entry) in history.iter().enumerate() {
+                    println!("{:>4}  {}", i + 1, entry);
+                }
+            }
+            Line::Help => {
+                let rows: Vec<(&str, String)> = HELP
+                    .iter()
+                    .map(|(usage, about)| (*usage, about.to_string()))
+                    .collect();
+                print_table(&rows);
+            }
+            Line::Exit => {}
+        }
+        Ok(())
+    }
+}
+
+// Best effort: the shell works without a writable home
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+// Oldest first, as `history` numbers them
+fn history_entries(history: &dyn History) -> Vec<String> {
+    (0..history.len())
+        .filter_map(|i| history.get(i, SearchDirection::Forward).ok().flatten())
+        .map(|found| found.entry.into_owned())
+        .collect()
+}
+
+pub async fn run(args: ShellArgs, rpc_url: &str) -> Result<()> {
+    let mut session = Session {
+        rpc_url: rpc_url.to_string(),
+        contract: args.contract,
+        block: args.block,
+        compiler: args.compiler,
+        last_word: None,
+    };
+    // The editor keeps the last HISTORY_LIMIT lines, in memory and in the file
+    let config = Config::builder()
+        .max_history_size(HISTORY_LIMIT)?
+        .auto_add_history(false)
+        .build();
+    let mut editor: Editor<CommandCompleter, DefaultHistory> = Editor::with_config(config)?;
+    editor.set_helper(Some(CommandCompleter));
+    let path = history_path();
+    if let Some(path) = &path {
+        // No file yet on the first run
+        let _ = editor.load_history(path);
+    }
+
+    println!("--- STORAGE SHELL (help for commands) ---");
+    session.print_context();
+
+    loop {
+        let prompt = session.prompt();
+        // The editor blocks on the terminal, keep it off the runtime's async workers
+        let raw = match tokio::task::block_in_place(|| editor.readline(&prompt)) {
+            Ok(raw) => raw,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => {
+                println!();
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let history = history_entries(editor.history());
+
+        let line = match expand_history(raw.trim(), &history) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                continue;
+            }
+        };
+        if line != raw.trim() {
+            println!("{}", line);
+        }
+
+        let parsed = match parse_line(&line) {
+            Ok(Some(parsed)) => parsed,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                continue;
+            }
+        };
+        if parsed == Line::Exit {
+            return Ok(());
+        }
+        if parsed != Line::History {
+            editor.add_history_entry(line.as_str())?;
+            if let Some(path) = &path {
+                let _ = editor.save_history(path);
+            }
+        }
+        let history = history_entries(editor.history());
+
+        if let Err(e) = session.execute(parsed, &history).await {
+            eprintln!("Error: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shell_lines() -> Result<()> {
+        let holder: MappingKey = "0xF977814e90dA44bFA03b6295A0616a897441aceC".parse()?;
+        assert_eq!(
+            parse_line("map 3 0xF977814e90dA44bFA03b6295A0616a897441aceC")?,
+            Some(Line::Map(Slot::from(3), vec![holder]))
+        );
+        assert_eq!(
+            parse_line("dec uint112 @14")?,
+            Some(Line::Decode(ValueType::Uint(112), 14))
+        );
+        assert_eq!(parse_line("  ")?, None);
+        assert!(parse_line("c 0xabc").is_err());
+        assert!(parse_line("slot").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_expansion_and_completion() -> Result<()> {
+        let history = vec!["slot 3".to_string(), "map 3 0xabc".to_string()];
+        assert_eq!(expand_history("!!", &history)?, "map 3 0xabc");
+        assert_eq!(expand_history("!1", &history)?, "slot 3");
+        assert!(expand_history("!0", &history).is_err());
+        assert_eq!(completions("co"), vec!["contract", "compiler"]);
+        assert_eq!(resolve("ma")?, "map");
+
+        let editor_history = DefaultHistory::new();
+        let ctx = rustyline::Context::new(&editor_history);
+        assert_eq!(
+            CommandCompleter.complete("  de", 4, &ctx)?,
+            (2, vec!["decode"])
+        );
+        assert_eq!(CommandCompleter.complete("map 3 c", 7, &ctx)?.1.len(), 0);
+        Ok(())
+    }
+}
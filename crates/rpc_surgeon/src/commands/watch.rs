@@ -0,0 +1,168 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{
+    BlockId, Compiler, DecodedValue, MappingKey, RpcError, Slot, Threshold, ValueType,
+    block_number, decode_field, delta, derive_slot_path_for, field_value, get_storage_at,
+    parse_limit, parse_word,
+};
+use std::time::Duration;
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Declared slot of the variable, decimal or 0x-prefixed 256-bit hex
+    #[arg(short, long)]
+    slot: Slot,
+
+    /// Mapping keys, outermost first (repeat for nested mappings)
+    #[arg(short, long = "key")]
+    keys: Vec<MappingKey>,
+
+    /// Storage convention used to derive the slot (solidity, vyper)
+    #[arg(long, default_value = "solidity")]
+    compiler: Compiler,
+
+    /// Type of the watched value
+    #[arg(short = 't', long = "type", default_value = "uint256")]
+    value_type: ValueType,
+
+    /// Byte offset of the field from the low-order end of the word (packed slots)
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+
+    /// Alert when the value rises above this (negative for intN types)
+    #[arg(long, allow_hyphen_values = true)]
+    above: Option<String>,
+
+    /// Alert when the value falls below this (negative for intN types)
+    #[arg(long, allow_hyphen_values = true)]
+    below: Option<String>,
+
+    /// Alert on every change
+    #[arg(long)]
+    any_change: bool,
+
+    /// Shell command run on each alert (SURGEON_BLOCK, SURGEON_SLOT, SURGEON_OLD, SURGEON_NEW
+    /// are set). Without it the first alert exits with an error.
+    #[arg(long)]
+    exec: Option<String>,
+
+    /// Milliseconds between eth_blockNumber polls
+    #[arg(long, default_value_t = 2000)]
+    interval: u64,
+}
+
+pub async fn run(args: WatchArgs, rpc_url: &str) -> Result<()> {
+    let target_slot = derive_slot_path_for(args.slot, &args.keys, args.compiler);
+    let size = args.value_type.size();
+    // intN fields are compared, and their deltas taken, as signed integers
+    let signed = matches!(args.value_type, ValueType::Int(_));
+
+    let mut thresholds = Vec::new();
+    if let Some(limit) = &args.above {
+        thresholds.push(Threshold::Above(parse_limit(limit, signed)?));
+    }
+    if let Some(limit) = &args.below {
+        thresholds.push(Threshold::Below(parse_limit(limit, signed)?));
+    }
+    if args.any_change {
+        thresholds.push(Threshold::AnyChange);
+    }
+
+    println!("--- WATCHING {} ---", target_slot);
+    let mut last_block = None;
+    let mut previous: Option<(Slot, DecodedValue)> = None;
+
+    loop {
+        match read_at_new_block(rpc_url, &args.contract, target_slot, last_block).await {
+            Ok(Some((block, word))) => {
+                last_block = Some(block);
+                let value = field_value(&word, args.offset, size, signed)?;
+                let decoded = decode_field(&word, args.offset, None, args.value_type)?;
+
+                match &previous {
+                    None => println!("Block {}: {}", block, decoded),
+                    Some((old, old_decoded)) if *old != value => {
+                        match args.value_type {
+                            ValueType::Uint(_) | ValueType::Int(_) => println!(
+                                "Block {}: {} -> {} ({})",
+                                block,
+                                old_decoded,
+                                decoded,
+                                delta(old, &value, signed)
+                            ),
+                            _ => println!("Block {}: {} -> {}", block, old_decoded, decoded),
+                        }
+
+                        if let Some(threshold) =
+                            thresholds.iter().find(|t| t.crossed(old, &value, signed))
+                        {
+                            let alerted = threshold.describe(signed);
+                            alert(&args, block, target_slot, old_decoded, &decoded, &alerted)
+                                .await?;
+                        }
+                    }
+                    Some(_) => {}
+                }
+                previous = Some((value, decoded));
+            }
+            Ok(None) => {}
+            // Keep watching through rate limits and flaky connections
+            Err(e)
+                if e.downcast_ref::<RpcError>()
+                    .is_some_and(RpcError::is_retryable) =>
+            {
+                eprintln!("Warning: {:#}", e);
+            }
+            Err(e) => return Err(e),
+        }
+
+        tokio::time::sleep(Duration::from_millis(args.interval)).await;
+    }
+}
+
+// The word at the head block, or None if no block was produced since `last_block`
+async fn read_at_new_block(
+    rpc_url: &str,
+    contract: &str,
+    slot: Slot,
+    last_block: Option<u64>,
+) -> Result<Option<(u64, [u8; 32])>> {
+    let block = block_number(rpc_url).await?;
+    if last_block.is_some_and(|last| block <= last) {
+        return Ok(None);
+    }
+    let raw = get_storage_at(rpc_url, contract, &slot.to_string(), BlockId::Number(block)).await?;
+    Ok(Some((block, parse_word(&raw)?)))
+}
+
+async fn alert(
+    args: &WatchArgs,
+    block: u64,
+    slot: Slot,
+    old: &DecodedValue,
+    new: &DecodedValue,
+    threshold: &str,
+) -> Result<()> {
+    let Some(command) = &args.exec else {
+        anyhow::bail!("Alert: value {} at block {}", threshold, block);
+    };
+
+    let status = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("SURGEON_BLOCK", block.to_string())
+        .env("SURGEON_SLOT", slot.to_string())
+        // Same rendering as the printed values: signed, address, bool, ...
+        .env("SURGEON_OLD", old.to_string())
+        .env("SURGEON_NEW", new.to_string())
+        .status()
+        .await?;
+    println!("Alert: value {}, hook ran", threshold);
+    if !status.success() {
+        eprintln!("Warning: hook exited with {}", status);
+    }
+    Ok(())
+}
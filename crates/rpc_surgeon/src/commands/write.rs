@@ -0,0 +1,48 @@
+use anyhow::Result;
+use clap::Args;
+use rpc_surgeon::{
+    Compiler, DecodedValue, DevNode, MappingKey, Slot, derive_slot_path_for, detect_dev_node,
+    write_storage,
+};
+
+#[derive(Args, Debug)]
+pub struct WriteArgs {
+    #[arg(short, long)]
+    contract: String,
+
+    /// Declared slot of the variable, decimal or 0x-prefixed 256-bit hex
+    #[arg(short, long)]
+    slot: Slot,
+
+    /// Mapping keys, outermost first, e.g. `-k <HOLDER>` for a balance
+    #[arg(short, long = "key")]
+    keys: Vec<MappingKey>,
+
+    /// Storage convention of the contract (solidity, vyper)
+    #[arg(long, default_value = "solidity")]
+    compiler: Compiler,
+
+    /// New word, decimal or 0x-prefixed hex (1000000000000000000 for 1e18 raw units)
+    #[arg(long)]
+    value: Slot,
+
+    /// Dev node flavour (anvil, hardhat), detected from web3_clientVersion by default
+    #[arg(long)]
+    node: Option<DevNode>,
+}
+
+pub async fn run(args: WriteArgs, rpc_url: &str) -> Result<()> {
+    let node = match args.node {
+        Some(node) => node,
+        None => detect_dev_node(rpc_url).await?,
+    };
+    let target_slot = derive_slot_path_for(args.slot, &args.keys, args.compiler);
+
+    let stored = write_storage(rpc_url, node, &args.contract, target_slot, args.value.0).await?;
+
+    println!("--- STORAGE WRITE ({}) ---", node);
+    println!("Target Slot: {}", target_slot);
+    println!("Raw value: 0x{}", hex::encode(stored));
+    println!("Confirmed value: {}", DecodedValue::Uint(stored.to_vec()));
+    Ok(())
+}
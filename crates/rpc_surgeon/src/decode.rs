@@ -0,0 +1,273 @@
+use crate::key::type_width;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Solidity value types that can live inside a (possibly packed) storage word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// `uintN`, width in bits
+    Uint(usize),
+    /// `intN`, width in bits
+    Int(usize),
+    Address,
+    Bool,
+    /// `bytesN`, width in bytes
+    FixedBytes(usize),
+}
+
+impl ValueType {
+    /// Number of bytes the type occupies inside a slot
+    pub fn size(&self) -> usize {
+        match self {
+            ValueType::Uint(bits) | ValueType::Int(bits) => bits / 8,
+            ValueType::Address => 20,
+            ValueType::Bool => 1,
+            ValueType::FixedBytes(size) => *size,
+        }
+    }
+}
+
+impl FromStr for ValueType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "address" => Ok(ValueType::Address),
+            "bool" => Ok(ValueType::Bool),
+            _ if s.starts_with("uint") => type_width(s, "uint", 8, 256).map(ValueType::Uint),
+            _ if s.starts_with("int") => type_width(s, "int", 8, 256).map(ValueType::Int),
+            _ if s.starts_with("bytes") && s != "bytes" => {
+                type_width(s, "bytes", 1, 32).map(ValueType::FixedBytes)
+            }
+            _ => anyhow::bail!("Unsupported value type: {}", s),
+        }
+    }
+}
+
+/// A value extracted from a storage word
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodedValue {
+    /// Big-endian magnitude
+    Uint(Vec<u8>),
+    /// Big-endian two's complement
+    Int(Vec<u8>),
+    Address([u8; 20]),
+    Bool(bool),
+    FixedBytes(Vec<u8>),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl fmt::Display for DecodedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedValue::Uint(bytes) => write!(f, "{}", to_decimal(bytes)),
+            DecodedValue::Int(bytes) => {
+                if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+                    write!(f, "-{}", to_decimal(&negate(bytes)))
+                } else {
+                    write!(f, "{}", to_decimal(bytes))
+                }
+            }
+            DecodedValue::Address(addr) => write!(f, "0x{}", hex::encode(addr)),
+            DecodedValue::Bool(flag) => write!(f, "{}", flag),
+            DecodedValue::FixedBytes(bytes) | DecodedValue::Bytes(bytes) => {
+                write!(f, "0x{}", hex::encode(bytes))
+            }
+            DecodedValue::String(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// Raw `eth_getStorageAt` hex into a 32-byte word (left-padded if the node trims zeros)
+pub fn parse_word(raw_hex: &str) -> Result<[u8; 32]> {
+    let clean = raw_hex.trim_start_matches("0x");
+    anyhow::ensure!(clean.len() <= 64, "Storage word too long: {}", raw_hex);
+    let padded = format!("{:0>64}", clean);
+
+    let mut word = [0u8; 32];
+    hex::decode_to_slice(&padded, &mut word)
+        .with_context(|| format!("Invalid storage word: {}", raw_hex))?;
+    Ok(word)
+}
+
+/// `size` bytes of the word starting `offset` bytes from the low-order end,
+/// matching solc's `offset` in packed slots
+pub fn extract_field(word: &[u8; 32], offset: usize, size: usize) -> Result<&[u8]> {
+    anyhow::ensure!(
+        size > 0 && offset.checked_add(size).is_some_and(|end| end <= 32),
+        "Field (offset {}, size {}) does not fit in a 32-byte slot",
+        offset,
+        size
+    );
+    let end = 32 - offset;
+    Ok(&word[end - size..end])
+}
+
+/// Extract a packed field and interpret it. `size` overrides the type's natural width.
+pub fn decode_field(
+    word: &[u8; 32],
+    offset: usize,
+    size: Option<usize>,
+    ty: ValueType,
+) -> Result<DecodedValue> {
+    let field = extract_field(word, offset, size.unwrap_or(ty.size()))?;
+
+    let value = match ty {
+        ValueType::Uint(_) => DecodedValue::Uint(field.to_vec()),
+        ValueType::Int(_) => DecodedValue::Int(field.to_vec()),
+        ValueType::Bool => DecodedValue::Bool(field.iter().any(|b| *b != 0)),
+        ValueType::FixedBytes(_) => DecodedValue::FixedBytes(field.to_vec()),
+        ValueType::Address => {
+            anyhow::ensure!(field.len() >= 20, "Address field needs 20 bytes");
+            let mut addr = [0u8; 20];
+            addr.copy_from_slice(&field[field.len() - 20..]);
+            DecodedValue::Address(addr)
+        }
+    };
+    Ok(value)
+}
+
+/// How a `string` / `bytes` variable is laid out, read from its head slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytesLayout {
+    /// Up to 31 bytes stored in the head slot itself (low byte = length * 2)
+    Short(Vec<u8>),
+    /// Head slot holds length * 2 + 1, data spans slots from keccak256(slot)
+    Long(usize),
+}
+
+impl BytesLayout {
+    pub fn from_word(word: &[u8; 32]) -> Result<Self> {
+        if word[31] & 1 == 0 {
+            let len = usize::from(word[31] / 2);
+            anyhow::ensure!(len < 32, "Invalid short string length: {}", len);
+            return Ok(BytesLayout::Short(word[..len].to_vec()));
+        }
+
+        anyhow::ensure!(
+            word[..24].iter().all(|b| *b == 0),
+            "Long string length does not fit in 64 bits"
+        );
+        let mut raw = [0u8; 8];
+        raw.copy_from_slice(&word[24..]);
+        let len = usize::try_from(u64::from_be_bytes(raw) / 2).context("String too long")?;
+        anyhow::ensure!(len >= 32, "Invalid long string length: {}", len);
+        Ok(BytesLayout::Long(len))
+    }
+
+    /// Number of data slots to fetch after the head slot
+    pub fn data_slots(&self) -> usize {
+        match self {
+            BytesLayout::Short(_) => 0,
+            BytesLayout::Long(len) => len.div_ceil(32),
+        }
+    }
+}
+
+/// UTF-8 text when possible, raw bytes otherwise
+pub fn decode_string(data: Vec<u8>) -> DecodedValue {
+    match String::from_utf8(data) {
+        Ok(s) => DecodedValue::String(s),
+        Err(e) => DecodedValue::Bytes(e.into_bytes()),
+    }
+}
+
+/// Big-endian unsigned bytes to a base-10 string
+fn to_decimal(bytes: &[u8]) -> String {
+    let mut value = bytes.to_vec();
+    let mut digits = Vec::new();
+
+    while value.iter().any(|b| *b != 0) {
+        // value /= 10, collecting the remainder
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let acc = (remainder << 8) | u32::from(*byte);
+            *byte = (acc / 10) as u8;
+            remainder = acc % 10;
+        }
+        digits.push(char::from(b'0' + remainder as u8));
+    }
+
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.iter().rev().collect()
+}
+
+// Two's complement magnitude of a negative value
+fn negate(bytes: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = bytes.iter().map(|b| !b).collect();
+    for byte in out.iter_mut().rev() {
+        let (sum, overflow) = byte.overflowing_add(1);
+        *byte = sum;
+        if !overflow {
+            break;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniswap_v2_packed_reserves() -> Result<()> {
+        // blockTimestampLast (uint32) | reserve1 (uint112) | reserve0 (uint112)
+        let word =
+            parse_word("0x65a1b2c300000000000000000000000f424000000000000000000000000003e8")?;
+
+        let reserve0 = decode_field(&word, 0, None, "uint112".parse()?)?;
+        let reserve1 = decode_field(&word, 14, None, "uint112".parse()?)?;
+        let timestamp = decode_field(&word, 28, None, "uint32".parse()?)?;
+
+        assert_eq!(reserve0.to_string(), "1000");
+        assert_eq!(reserve1.to_string(), "1000000");
+        assert_eq!(timestamp.to_string(), "1705095875");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_full_word_and_signed() -> Result<()> {
+        let max = parse_word(&format!("0x{}", "ff".repeat(32)))?;
+        assert_eq!(
+            decode_field(&max, 0, None, ValueType::Uint(256))?.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(
+            decode_field(&max, 3, None, "int24".parse()?)?.to_string(),
+            "-1"
+        );
+        assert_eq!(
+            decode_field(&max, 0, None, ValueType::Bool)?.to_string(),
+            "true"
+        );
+        assert!(extract_field(&max, 20, 16).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_and_long_string_layouts() -> Result<()> {
+        // "Wrapped Ether" (13 bytes) -> low byte 0x1a
+        let mut word = [0u8; 32];
+        word[..13].copy_from_slice(b"Wrapped Ether");
+        word[31] = 0x1a;
+        let layout = BytesLayout::from_word(&word)?;
+        assert_eq!(layout, BytesLayout::Short(b"Wrapped Ether".to_vec()));
+        assert_eq!(layout.data_slots(), 0);
+
+        // 100 bytes -> 201 in the head slot, 4 data slots
+        let long = parse_word("0xc9")?;
+        let layout = BytesLayout::from_word(&long)?;
+        assert_eq!(layout, BytesLayout::Long(100));
+        assert_eq!(layout.data_slots(), 4);
+
+        assert_eq!(
+            decode_string(vec![0xff, 0x00]),
+            DecodedValue::Bytes(vec![0xff, 0x00])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use crate::batch::{BatchOptions, StorageRequest, get_storage_batch};
+use crate::decode::parse_word;
+use crate::rpc::{BlockId, resolve_block};
+use crate::slot::Slot;
+use crate::storage_range::{RangeEntry, storage_dump};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+
+/// A slot whose value differs between two blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotDiff {
+    /// Unknown when the node returned a hashed key without its preimage
+    pub slot: Option<Slot>,
+    /// Secure trie key, keccak256(slot)
+    pub hashed_key: Slot,
+    pub before: [u8; 32],
+    pub after: [u8; 32],
+}
+
+/// Compare the given slots at `from` and `to`, keeping only the ones that changed.
+/// Tags are pinned to one number first, so every batch reads the same two snapshots.
+pub async fn diff_slots(
+    rpc_url: &str,
+    address: &str,
+    slots: &[Slot],
+    from: BlockId,
+    to: BlockId,
+    options: BatchOptions,
+) -> Result<Vec<SlotDiff>> {
+    let from = resolve_block(rpc_url, from).await?;
+    let to = resolve_block(rpc_url, to).await?;
+    let requests: Vec<StorageRequest> = [from, to]
+        .iter()
+        .flat_map(|block| {
+            slots.iter().map(|slot| StorageRequest {
+                address: address.to_string(),
+                slot: *slot,
+                block: *block,
+            })
+        })
+        .collect();
+
+    let mut words = Vec::with_capacity(requests.len());
+    for (request, raw) in requests
+        .iter()
+        .zip(get_storage_batch(rpc_url, &requests, options).await)
+    {
+        let raw = raw.with_context(|| {
+            format!("Failed to read {} at block {}", request.slot, request.block)
+        })?;
+        words.push(parse_word(&raw)?);
+    }
+
+    let (before, after) = words.split_at(slots.len());
+    Ok(slots
+        .iter()
+        .zip(before.iter().zip(after))
+        .filter(|(_, (before, after))| before != after)
+        .map(|(slot, (before, after))| SlotDiff {
+            slot: Some(*slot),
+            hashed_key: slot.hashed(),
+            before: *before,
+            after: *after,
+        })
+        .collect())
+}
+
+/// Diff every slot of `address` between the end of blocks `from` and `to` (`debug_storageRangeAt`)
+pub async fn diff_storage(
+    rpc_url: &str,
+    address: &str,
+    from: u64,
+    to: u64,
+    page_size: usize,
+) -> Result<Vec<SlotDiff>> {
+    let before = storage_dump(rpc_url, address, from, page_size).await?;
+    let after = storage_dump(rpc_url, address, to, page_size).await?;
+    Ok(diff_ranges(&before, &after))
+}
+
+/// Changed, created and cleared slots between two storage dumps, in trie key order
+pub fn diff_ranges(before: &[RangeEntry], after: &[RangeEntry]) -> Vec<SlotDiff> {
+    let mut merged: BTreeMap<Slot, SlotDiff> = BTreeMap::new();
+
+    for entry in before {
+        merged.insert(
+            entry.hashed_key,
+            SlotDiff {
+                slot: entry.key,
+                hashed_key: entry.hashed_key,
+                before: entry.value,
+                after: [0u8; 32],
+            },
+        );
+    }
+    for entry in after {
+        let diff = merged.entry(entry.hashed_key).or_insert(SlotDiff {
+            slot: entry.key,
+            hashed_key: entry.hashed_key,
+            before: [0u8; 32],
+            after: [0u8; 32],
+        });
+        diff.slot = diff.slot.or(entry.key);
+        diff.after = entry.value;
+    }
+
+    merged
+        .into_values()
+        .filter(|diff| diff.before != diff.after)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(slot: u64, value: u8) -> RangeEntry {
+        let mut word = [0u8; 32];
+        word[31] = value;
+        RangeEntry {
+            hashed_key: Slot::from(slot).hashed(),
+            key: Some(Slot::from(slot)),
+            value: word,
+        }
+    }
+
+    #[test]
+    fn test_diff_ranges_changed_created_cleared() {
+        let before = [entry(0, 1), entry(1, 2), entry(2, 3)];
+        let after = [entry(0, 1), entry(1, 5), entry(3, 4)];
+
+        let diffs = diff_ranges(&before, &after);
+        let mut changed: Vec<(Option<Slot>, u8, u8)> = diffs
+            .iter()
+            .map(|d| (d.slot, d.before[31], d.after[31]))
+            .collect();
+        changed.sort();
+
+        assert_eq!(
+            changed,
+            vec![
+                (Some(Slot::from(1)), 2, 5),
+                (Some(Slot::from(2)), 3, 0),
+                (Some(Slot::from(3)), 0, 4),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_latest_pinned_once_for_all_chunks() -> Result<()> {
+        use crate::rpc::tests::mock_node;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        // The head moves on every eth_blockNumber, slots echo the block they were read at
+        let head = AtomicU64::new(100);
+        let rpc = mock_node(move |method, params| match method {
+            "eth_blockNumber" => {
+                serde_json::json!(format!("0x{:x}", head.fetch_add(1, Ordering::SeqCst)))
+            }
+            "eth_getStorageAt" => params[2].clone(),
+            _ => serde_json::Value::Null,
+        })
+        .await?;
+
+        let slots: Vec<Slot> = (0..4).map(Slot::from).collect();
+        let options = BatchOptions {
+            chunk_size: 1,
+            concurrency: 3,
+        };
+        let diffs = diff_slots(
+            &rpc,
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            &slots,
+            BlockId::Number(50),
+            BlockId::Latest,
+            options,
+        )
+        .await?;
+
+        assert_eq!(diffs.len(), 4);
+        for diff in &diffs {
+            assert_eq!(
+                (diff.before, diff.after),
+                (Slot::from(50).0, Slot::from(100).0)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use crate::decode::parse_word;
+use crate::key::{MappingKey, parse_address};
+use crate::rpc::{BlockId, call, eth_call, get_storage_at};
+use crate::slot::{Compiler, Slot};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+// balanceOf(address)
+const BALANCE_OF_SELECTOR: &str = "70a08231";
+
+/// A mapping position whose entry for the holder matches `balanceOf`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceSlot {
+    pub position: u64,
+    pub compiler: Compiler,
+    /// Derived storage slot of the holder's balance
+    pub slot: Slot,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Discovery {
+    pub balance: [u8; 32],
+    pub matches: Vec<BalanceSlot>,
+    /// Slots read by `balanceOf` holding the balance but not derivable from
+    /// positions 0..max (namespaced storage, nested structs, ...)
+    pub unexplained: Vec<Slot>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListResult {
+    access_list: Vec<AccessListItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListItem {
+    address: String,
+    storage_keys: Vec<String>,
+}
+
+fn balance_of_calldata(holder: &str) -> Result<String> {
+    let holder = parse_address(holder)?;
+    Ok(format!(
+        "0x{}{:0>64}",
+        BALANCE_OF_SELECTOR,
+        hex::encode(holder)
+    ))
+}
+
+pub async fn balance_of(
+    rpc_url: &str,
+    token: &str,
+    holder: &str,
+    block: BlockId,
+) -> Result<[u8; 32]> {
+    let ret = eth_call(rpc_url, token, &balance_of_calldata(holder)?, block).await?;
+    parse_word(&ret).context("Unexpected balanceOf return data")
+}
+
+/// Storage slots of `token` touched by `balanceOf(holder)`, via `eth_createAccessList`
+pub async fn access_list_slots(
+    rpc_url: &str,
+    token: &str,
+    holder: &str,
+    block: BlockId,
+) -> Result<Vec<Slot>> {
+    let tx = serde_json::json!({ "to": token, "data": balance_of_calldata(holder)? });
+    let result: AccessListResult = call(
+        rpc_url,
+        "eth_createAccessList",
+        serde_json::json!([tx, block.to_param()]),
+    )
+    .await?;
+
+    let mut slots = Vec::new();
+    for item in result
+        .access_list
+        .iter()
+        .filter(|item| item.address.eq_ignore_ascii_case(token))
+    {
+        for key in &item.storage_keys {
+            slots.push(key.parse()?);
+        }
+    }
+    Ok(slots)
+}
+
+/// Every (position, compiler) candidate for the holder's balance slot in 0..max_position
+pub fn candidate_slots(holder: &MappingKey, max_position: u64) -> Vec<BalanceSlot> {
+    (0..max_position)
+        .flat_map(|position| {
+            [Compiler::Solidity, Compiler::Vyper].map(|compiler| BalanceSlot {
+                position,
+                compiler,
+                slot: Slot::from(position).mapping_for(holder, compiler),
+            })
+        })
+        .collect()
+}
+
+/// Brute-force the balance mapping position of an ERC-20 by comparing candidate
+/// slots against `balanceOf(holder)`. With `use_access_list`, only slots the
+/// call actually reads are checked.
+pub async fn discover_balance_slot(
+    rpc_url: &str,
+    token: &str,
+    holder: &str,
+    max_position: u64,
+    use_access_list: bool,
+    block: BlockId,
+) -> Result<Discovery> {
+    let balance = balance_of(rpc_url, token, holder, block).await?;
+    anyhow::ensure!(
+        balance != [0u8; 32],
+        "Holder has a zero balance, pick one with tokens to get a meaningful match"
+    );
+
+    let key = MappingKey::Address(parse_address(holder)?);
+    let mut candidates = candidate_slots(&key, max_position);
+    let mut discovery = Discovery {
+        balance,
+        ..Default::default()
+    };
+
+    if use_access_list {
+        let touched = access_list_slots(rpc_url, token, holder, block).await?;
+        candidates.retain(|c| touched.contains(&c.slot));
+
+        for slot in touched {
+            if candidates.iter().any(|c| c.slot == slot) {
+                continue;
+            }
+            let raw = get_storage_at(rpc_url, token, &slot.to_string(), block).await?;
+            if parse_word(&raw)? == balance {
+                discovery.unexplained.push(slot);
+            }
+        }
+    }
+
+    for candidate in candidates {
+        let raw = get_storage_at(rpc_url, token, &candidate.slot.to_string(), block).await?;
+        if parse_word(&raw)? == balance {
+            discovery.matches.push(candidate);
+        }
+    }
+    Ok(discovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_cover_both_orderings() -> Result<()> {
+        let holder: MappingKey = "0xF977814e90dA44bFA03b6295A0616a897441aceC".parse()?;
+        let candidates = candidate_slots(&holder, 5);
+        assert_eq!(candidates.len(), 10);
+
+        // WETH balanceOf is the Solidity mapping at position 3
+        let weth = candidates
+            .iter()
+            .find(|c| c.position == 3 && c.compiler == Compiler::Solidity)
+            .context("missing candidate")?;
+        assert_eq!(
+            weth.slot.to_string(),
+            "0x9cca97fb08ee88532e0983a3a051466c5df908292b6899f3cdc163eb9c0b22ba"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_balance_of_calldata() -> Result<()> {
+        assert_eq!(
+            balance_of_calldata("0xF977814e90dA44bFA03b6295A0616a897441aceC")?,
+            "0x70a08231000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+use crate::derive_slot_path_for;
+use crate::key::MappingKey;
+use crate::slot::{Compiler, Slot};
+use crate::storage_range::RangeEntry;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Where a dumped slot most likely comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotOrigin {
+    /// A declared variable (or struct member / Vyper array word) at a small slot
+    Direct(u64),
+    /// `mapping[key]` for the mapping declared at `position`
+    Mapping {
+        position: u64,
+        key: MappingKey,
+        compiler: Compiler,
+    },
+    /// `mapping[outer][inner]` for the nested mapping declared at `position`
+    NestedMapping {
+        position: u64,
+        keys: [MappingKey; 2],
+        compiler: Compiler,
+    },
+    /// Word `word` of the dynamic array / long string whose head is at `position`
+    ArrayWord {
+        position: u64,
+        word: u64,
+    },
+    Unknown,
+}
+
+impl fmt::Display for SlotOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotOrigin::Direct(position) => write!(f, "slot {}", position),
+            SlotOrigin::Mapping {
+                position,
+                key,
+                compiler,
+            } => write!(f, "mapping {}[{}] ({})", position, key, compiler),
+            SlotOrigin::NestedMapping {
+                position,
+                keys: [outer, inner],
+                compiler,
+            } => write!(
+                f,
+                "mapping {}[{}][{}] ({})",
+                position, outer, inner, compiler
+            ),
+            SlotOrigin::ArrayWord { position, word } => {
+                write!(f, "array {} word {}", position, word)
+            }
+            SlotOrigin::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// A dumped slot with its recovered preimage and origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpEntry {
+    pub hashed_key: Slot,
+    /// Slot preimage, from the node or recovered from the candidates
+    pub slot: Option<Slot>,
+    pub value: [u8; 32],
+    pub origin: SlotOrigin,
+}
+
+/// Search space used to reverse hashed slots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreimageCandidates {
+    /// Mapping keys to try (known addresses, ids, ...)
+    pub keys: Vec<MappingKey>,
+    /// `(outer, inner)` keys to try on two-level mappings (`allowance[owner][spender]`)
+    pub key_pairs: Vec<(MappingKey, MappingKey)>,
+    /// Declared positions `0..max_position` tried as mapping and array heads
+    pub max_position: u64,
+    /// Array words `0..max_index` tried after each array head
+    pub max_index: u64,
+}
+
+impl Default for PreimageCandidates {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            key_pairs: Vec::new(),
+            max_position: 64,
+            max_index: 1024,
+        }
+    }
+}
+
+impl PreimageCandidates {
+    /// Also try the integers `0..n` as `uint256` mapping keys
+    pub fn with_small_ints(mut self, n: u64) -> Self {
+        self.keys
+            .extend((0..n).map(|i| MappingKey::Uint(Slot::from(i).0)));
+        self
+    }
+}
+
+/// Attach a slot preimage and an origin to each `debug_storageRangeAt` entry
+pub fn annotate_dump(entries: &[RangeEntry], candidates: &PreimageCandidates) -> Vec<DumpEntry> {
+    let heads: Vec<(u64, Slot)> = (0..candidates.max_position)
+        .map(|position| (position, Slot::from(position).hashed()))
+        .collect();
+
+    // Integer keys are ambiguous across compilers (keccak(h(5) . 7) is also Vyper's
+    // position 5, key 7), Solidity goes first and keeps the slot
+    let mut mappings: HashMap<Slot, SlotOrigin> = HashMap::new();
+    for compiler in [Compiler::Solidity, Compiler::Vyper] {
+        for position in 0..candidates.max_position {
+            for key in &candidates.keys {
+                mappings
+                    .entry(Slot::from(position).mapping_for(key, compiler))
+                    .or_insert_with(|| SlotOrigin::Mapping {
+                        position,
+                        key: key.clone(),
+                        compiler,
+                    });
+            }
+            for (outer, inner) in &candidates.key_pairs {
+                let keys = [outer.clone(), inner.clone()];
+                mappings
+                    .entry(derive_slot_path_for(Slot::from(position), &keys, compiler))
+                    .or_insert_with(|| SlotOrigin::NestedMapping {
+                        position,
+                        keys,
+                        compiler,
+                    });
+            }
+        }
+    }
+
+    // Nodes without preimages only return keccak256(slot): hash every candidate slot
+    let hashed: HashMap<Slot, Slot> = if entries.iter().any(|entry| entry.key.is_none()) {
+        let direct = (0..candidates.max_position).map(Slot::from);
+        let words = heads
+            .iter()
+            .flat_map(|(_, head)| (0..candidates.max_index).map(|word| head.offset(word)));
+        direct
+            .chain(words)
+            .chain(mappings.keys().copied())
+            .map(|slot| (slot.hashed(), slot))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    entries
+        .iter()
+        .map(|entry| {
+            let slot = entry.key.or_else(|| hashed.get(&entry.hashed_key).copied());
+            let origin = match slot {
+                Some(slot) => classify(slot, &heads, &mappings, candidates.max_index),
+                None => SlotOrigin::Unknown,
+            };
+            DumpEntry {
+                hashed_key: entry.hashed_key,
+                slot,
+                value: entry.value,
+                origin,
+            }
+        })
+        .collect()
+}
+
+fn classify(
+    slot: Slot,
+    heads: &[(u64, Slot)],
+    mappings: &HashMap<Slot, SlotOrigin>,
+    max_index: u64,
+) -> SlotOrigin {
+    if let Some(position) = slot.to_u64() {
+        return SlotOrigin::Direct(position);
+    }
+    if let Some(origin) = mappings.get(&slot) {
+        return origin.clone();
+    }
+    heads
+        .iter()
+        .find_map(|(position, head)| {
+            let word = slot.wrapping_sub(head).to_u64()?;
+            (word < max_index).then_some(SlotOrigin::ArrayWord {
+                position: *position,
+                word,
+            })
+        })
+        .unwrap_or(SlotOrigin::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(slot: Slot, with_preimage: bool) -> RangeEntry {
+        RangeEntry {
+            hashed_key: slot.hashed(),
+            key: with_preimage.then_some(slot),
+            value: [1u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_annotate_recovers_hashed_keys() {
+        let holder = MappingKey::Address([0xab; 20]);
+        let candidates = PreimageCandidates {
+            keys: vec![holder.clone()],
+            key_pairs: Vec::new(),
+            max_position: 8,
+            max_index: 16,
+        }
+        .with_small_ints(10);
+
+        let balance = Slot::from(3).mapping(&holder);
+        let vyper_balance = Slot::from(5).mapping_for(&holder, Compiler::Vyper);
+        let element = Slot::from(6).hashed().offset(2);
+        let entries = [
+            entry(Slot::from(1), false),
+            entry(balance, false),
+            entry(vyper_balance, true),
+            entry(element, false),
+            entry(Slot::from(999).hashed().hashed(), false),
+        ];
+
+        let dump = annotate_dump(&entries, &candidates);
+        assert_eq!(dump[0].origin, SlotOrigin::Direct(1));
+        assert_eq!(dump[1].slot, Some(balance));
+        assert_eq!(
+            dump[1].origin,
+            SlotOrigin::Mapping {
+                position: 3,
+                key: holder,
+                compiler: Compiler::Solidity
+            }
+        );
+        assert!(matches!(
+            dump[2].origin,
+            SlotOrigin::Mapping {
+                position: 5,
+                compiler: Compiler::Vyper,
+                ..
+            }
+        ));
+        assert_eq!(
+            dump[3].origin,
+            SlotOrigin::ArrayWord {
+                position: 6,
+                word: 2
+            }
+        );
+        assert_eq!(
+            (dump[4].slot, &dump[4].origin),
+            (None, &SlotOrigin::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_annotate_nested_mapping() {
+        let owner = MappingKey::Address([0xab; 20]);
+        let spender = MappingKey::Address([0xcd; 20]);
+        let candidates = PreimageCandidates {
+            key_pairs: vec![(owner.clone(), spender.clone())],
+            max_position: 8,
+            ..PreimageCandidates::default()
+        };
+
+        // allowance[owner][spender] at position 4, one read with its preimage, one without
+        let allowance = Slot::from(4).mapping(&owner).mapping(&spender);
+        let dump = annotate_dump(
+            &[entry(allowance, true), entry(allowance, false)],
+            &candidates,
+        );
+        let expected = SlotOrigin::NestedMapping {
+            position: 4,
+            keys: [owner, spender],
+            compiler: Compiler::Solidity,
+        };
+        assert_eq!(dump[0].origin, expected);
+        assert_eq!(
+            (dump[1].slot, &dump[1].origin),
+            (Some(allowance), &expected)
+        );
+        assert!(expected.to_string().starts_with("mapping 4[0xabab"));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::rpc::{BlockId, get_storage_at};
+use crate::slot::Slot;
+use anyhow::Result;
+
+/// A slot value observed from `block` onward
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotChange {
+    pub block: u64,
+    pub raw: String,
+}
+
+/// Read `slot` every `step` blocks in `from..=to` and keep only the blocks where it changed.
+/// The first entry is the value at `from`.
+pub async fn storage_history(
+    rpc_url: &str,
+    address: &str,
+    slot: Slot,
+    from: u64,
+    to: u64,
+    step: u64,
+) -> Result<Vec<SlotChange>> {
+    anyhow::ensure!(from <= to, "Empty block range {}..{}", from, to);
+    anyhow::ensure!(step > 0, "Step must be at least 1");
+
+    let slot = slot.to_string();
+    let mut changes: Vec<SlotChange> = Vec::new();
+    let mut block = from;
+
+    loop {
+        let raw = get_storage_at(rpc_url, address, &slot, BlockId::Number(block)).await?;
+        if changes.last().is_none_or(|last| last.raw != raw) {
+            changes.push(SlotChange { block, raw });
+        }
+
+        // Steps that overshoot still end on `to`
+        match block.checked_add(step) {
+            Some(next) if next <= to => block = next,
+            _ if block < to => block = to,
+            _ => break,
+        }
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::tests::mock_node;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_unaligned_range_reads_last_block() -> Result<()> {
+        // Value is 1 before block 15, 2 from there on; record every block asked for
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&asked);
+        let rpc = mock_node(move |_, params| {
+            let block = u64::from_str_radix(
+                params[2]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim_start_matches("0x"),
+                16,
+            )
+            .unwrap_or_default();
+            if let Ok(mut log) = log.lock() {
+                log.push(block);
+            }
+            serde_json::json!(format!("0x{:x}", if block < 15 { 1 } else { 2 }))
+        })
+        .await?;
+
+        let changes = storage_history(&rpc, "0xabc", Slot::from(0), 10, 17, 3).await?;
+
+        let mut blocks = asked
+            .lock()
+            .map_err(|_| anyhow::anyhow!("poisoned"))?
+            .clone();
+        blocks.sort_unstable();
+        assert_eq!(blocks, vec![10, 13, 16, 17]);
+        // 13 repeats 10 and 17 repeats 16: only the changes are kept
+        assert_eq!(
+            changes,
+            vec![
+                SlotChange {
+                    block: 10,
+                    raw: "0x1".to_string()
+                },
+                SlotChange {
+                    block: 16,
+                    raw: "0x2".to_string()
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use crate::batch::{BatchOptions, StorageRequest, get_storage_batch};
+use crate::decode::parse_word;
+use crate::key::{MappingKey, parse_address};
+use crate::rpc::{BlockId, resolve_block};
+use crate::slot::{Compiler, Slot};
+use anyhow::{Context, Result};
+
+/// A holder's balance slot and the word read from it
+#[derive(Debug)]
+pub struct HolderBalance {
+    pub holder: [u8; 20],
+    pub slot: Slot,
+    pub word: Result<[u8; 32]>,
+}
+
+/// Holder addresses from a newline list, a CSV (address in the first column,
+/// optional header) or a JSON array of addresses / `{ "address": .. }` objects
+pub fn parse_holders(text: &str) -> Result<Vec<[u8; 20]>> {
+    if text.trim_start().starts_with('[') {
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(text).context("Invalid JSON holder list")?;
+        return entries
+            .iter()
+            .map(|entry| {
+                let address = entry
+                    .as_str()
+                    .or_else(|| entry.get("address").and_then(|a| a.as_str()))
+                    .with_context(|| format!("Expected an address, got {}", entry))?;
+                parse_address(address)
+            })
+            .collect();
+    }
+
+    let mut holders = Vec::new();
+    let mut first_row = true;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let is_first_row = std::mem::replace(&mut first_row, false);
+        let field = line
+            .split([',', ';', '\t'])
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .trim_matches('"');
+        match parse_address(field) {
+            Ok(holder) => holders.push(holder),
+            // Only the first row of a CSV may be a header, later bad rows are errors
+            Err(_) if is_first_row && !field.starts_with("0x") => continue,
+            Err(e) => return Err(e.context(format!("Line {}", i + 1))),
+        }
+    }
+    Ok(holders)
+}
+
+/// Derive each holder's slot in the balance mapping at `position` and read them all in batches.
+/// A tag `block` is pinned to one number first, so every batch reads the same snapshot.
+pub async fn holder_balances(
+    rpc_url: &str,
+    token: &str,
+    position: Slot,
+    holders: &[[u8; 20]],
+    compiler: Compiler,
+    block: BlockId,
+    options: BatchOptions,
+) -> Result<Vec<HolderBalance>> {
+    let block = resolve_block(rpc_url, block).await?;
+    let slots: Vec<Slot> = holders
+        .iter()
+        .map(|holder| position.mapping_for(&MappingKey::Address(*holder), compiler))
+        .collect();
+    let requests: Vec<StorageRequest> = slots
+        .iter()
+        .map(|slot| StorageRequest {
+            address: token.to_string(),
+            slot: *slot,
+            block,
+        })
+        .collect();
+
+    let words = get_storage_batch(rpc_url, &requests, options).await;
+
+    Ok(holders
+        .iter()
+        .zip(slots)
+        .zip(words)
+        .map(|((holder, slot), raw)| HolderBalance {
+            holder: *holder,
+            slot,
+            word: raw.and_then(|raw| parse_word(&raw)),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINANCE: &str = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
+    const ROUTER: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+
+    #[test]
+    fn test_parse_csv_and_newline_lists() -> Result<()> {
+        let csv = format!(
+            "address,label\n\"{}\",binance\n\n# skipped\n{};router\n",
+            BINANCE, ROUTER
+        );
+        let holders = parse_holders(&csv)?;
+        assert_eq!(
+            holders,
+            vec![parse_address(BINANCE)?, parse_address(ROUTER)?]
+        );
+
+        let json = format!(r#"["{}", {{ "address": "{}" }}]"#, BINANCE, ROUTER);
+        assert_eq!(parse_holders(&json)?, holders);
+
+        assert!(parse_holders(&format!("{}\nnot-an-address\n", BINANCE)).is_err());
+        // One header at most: a second unparsable row before any address is an error
+        assert!(parse_holders(&format!("address\ngarbage\n{}\n", BINANCE)).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_holder_slots_follow_mapping_derivation() -> Result<()> {
+        let holders = vec![parse_address(BINANCE)?];
+        let balances = holder_balances(
+            "http://127.0.0.1:1",
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            Slot::from(3),
+            &holders,
+            Compiler::Solidity,
+            BlockId::Number(19_000_000),
+            BatchOptions::default(),
+        )
+        .await?;
+
+        assert_eq!(
+            balances[0].slot.to_string(),
+            "0x9cca97fb08ee88532e0983a3a051466c5df908292b6899f3cdc163eb9c0b22ba"
+        );
+        assert!(balances[0].word.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_latest_pinned_once_for_all_batches() -> Result<()> {
+        use crate::rpc::tests::mock_node;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        // The head moves on every eth_blockNumber, slots echo the block they were read at
+        let head = AtomicU64::new(100);
+        let rpc = mock_node(move |method, params| match method {
+            "eth_blockNumber" => {
+                serde_json::json!(format!("0x{:x}", head.fetch_add(1, Ordering::SeqCst)))
+            }
+            "eth_getStorageAt" => params[2].clone(),
+            _ => serde_json::Value::Null,
+        })
+        .await?;
+
+        let holders = vec![parse_address(BINANCE)?, parse_address(ROUTER)?, [7u8; 20]];
+        let options = BatchOptions {
+            chunk_size: 1,
+            concurrency: 3,
+        };
+        let balances = holder_balances(
+            &rpc,
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            Slot::from(3),
+            &holders,
+            Compiler::Solidity,
+            BlockId::Latest,
+            options,
+        )
+        .await?;
+
+        for balance in &balances {
+            assert_eq!(balance.word.as_ref().ok(), Some(&Slot::from(100).0));
+        }
+        Ok(())
+    }
+}
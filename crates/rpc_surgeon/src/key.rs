@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A Solidity mapping key, encoded the way the compiler hashes it.
+///
+/// Value types are padded to a 32-byte word, `string` / `bytes` keys are hashed unpadded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingKey {
+    Address([u8; 20]),
+    /// Any `uintN`, stored as a big-endian word
+    Uint([u8; 32]),
+    /// Any `intN`, stored as a sign-extended big-endian word
+    Int([u8; 32]),
+    Bool(bool),
+    /// `bytesN` (1..=32), left-aligned with zero padding on the right
+    FixedBytes(Vec<u8>),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl MappingKey {
+    /// Bytes fed to keccak256 in front of the mapping position
+    pub fn encode(&self) -> Vec<u8> {
+        let mut word = [0u8; 32];
+        match self {
+            MappingKey::Address(addr) => word[12..].copy_from_slice(addr),
+            MappingKey::Uint(value) | MappingKey::Int(value) => word = *value,
+            MappingKey::Bool(flag) => word[31] = u8::from(*flag),
+            MappingKey::FixedBytes(bytes) => word[..bytes.len()].copy_from_slice(bytes),
+            MappingKey::String(s) => return s.as_bytes().to_vec(),
+            MappingKey::Bytes(bytes) => return bytes.clone(),
+        }
+        word.to_vec()
+    }
+}
+
+/// Parses `type:value` (e.g. `uint256:42`, `bytes32:0x..`, `string:USDC`).
+/// A bare value is read as an address to keep `--owner 0x..` working.
+impl FromStr for MappingKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((ty, value)) = s.split_once(':') else {
+            return parse_address(s).map(MappingKey::Address);
+        };
+
+        match ty {
+            "address" => parse_address(value).map(MappingKey::Address),
+            "bool" => match value {
+                "true" | "1" => Ok(MappingKey::Bool(true)),
+                "false" | "0" => Ok(MappingKey::Bool(false)),
+                _ => anyhow::bail!("Invalid bool key: {}", value),
+            },
+            "string" => Ok(MappingKey::String(value.to_string())),
+            "bytes" => decode_hex(value).map(MappingKey::Bytes),
+            _ if ty.starts_with("uint") => {
+                let bits = type_width(ty, "uint", 8, 256)?;
+                parse_uint(value, bits).map(MappingKey::Uint)
+            }
+            _ if ty.starts_with("int") => {
+                let bits = type_width(ty, "int", 8, 256)?;
+                parse_int(value, bits).map(MappingKey::Int)
+            }
+            _ if ty.starts_with("bytes") => {
+                let size = type_width(ty, "bytes", 1, 32)?;
+                let bytes = decode_hex(value)?;
+                anyhow::ensure!(
+                    bytes.len() == size,
+                    "{} key must be {} bytes, got {}",
+                    ty,
+                    size,
+                    bytes.len()
+                );
+                Ok(MappingKey::FixedBytes(bytes))
+            }
+            _ => anyhow::bail!("Unsupported mapping key type: {}", ty),
+        }
+    }
+}
+
+impl fmt::Display for MappingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MappingKey::Address(addr) => write!(f, "0x{}", hex::encode(addr)),
+            MappingKey::Uint(value) => write!(f, "uint256:0x{}", hex::encode(value)),
+            MappingKey::Int(value) => write!(f, "int256:0x{}", hex::encode(value)),
+            MappingKey::Bool(flag) => write!(f, "bool:{}", flag),
+            MappingKey::FixedBytes(bytes) => {
+                write!(f, "bytes{}:0x{}", bytes.len(), hex::encode(bytes))
+            }
+            MappingKey::String(s) => write!(f, "string:{}", s),
+            MappingKey::Bytes(bytes) => write!(f, "bytes:0x{}", hex::encode(bytes)),
+        }
+    }
+}
+
+pub(crate) fn parse_address(value: &str) -> Result<[u8; 20]> {
+    let bytes = decode_hex(value).context("Failed to decode user address")?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| anyhow::anyhow!("Address must be 20 bytes, got {}", b.len()))
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x")).with_context(|| format!("Invalid hex: {}", value))
+}
+
+// "uint64" -> 64, "uint" -> 256, "bytes4" -> 4
+pub(crate) fn type_width(ty: &str, prefix: &str, step: usize, max: usize) -> Result<usize> {
+    let suffix = &ty[prefix.len()..];
+    if suffix.is_empty() {
+        return Ok(max);
+    }
+    let width: usize = suffix
+        .parse()
+        .with_context(|| format!("Invalid type: {}", ty))?;
+    anyhow::ensure!(
+        width > 0 && width <= max && width.is_multiple_of(step),
+        "Invalid type: {}",
+        ty
+    );
+    Ok(width)
+}
+
+/// Decimal or `0x` hex unsigned integer into a big-endian word, bounded to `bits`
+pub(crate) fn parse_uint(value: &str, bits: usize) -> Result<[u8; 32]> {
+    let mut word = [0u8; 32];
+
+    if let Some(hex_digits) = value.strip_prefix("0x") {
+        let padded = format!("{:0>64}", hex_digits);
+        anyhow::ensure!(padded.len() == 64, "Integer key too large: {}", value);
+        hex::decode_to_slice(&padded, &mut word)
+            .with_context(|| format!("Invalid hex integer: {}", value))?;
+    } else {
+        anyhow::ensure!(!value.is_empty(), "Empty integer key");
+        for c in value.chars() {
+            let digit = c
+                .to_digit(10)
+                .with_context(|| format!("Invalid decimal integer: {}", value))?;
+            // word = word * 10 + digit
+            let mut carry = digit;
+            for byte in word.iter_mut().rev() {
+                let acc = u32::from(*byte) * 10 + carry;
+                *byte = acc as u8;
+                carry = acc >> 8;
+            }
+            anyhow::ensure!(carry == 0, "Integer key too large: {}", value);
+        }
+    }
+
+    let unused_bytes = (256 - bits) / 8;
+    anyhow::ensure!(
+        word[..unused_bytes].iter().all(|b| *b == 0),
+        "Integer key does not fit in uint{}: {}",
+        bits,
+        value
+    );
+    Ok(word)
+}
+
+/// Signed integer into a two's complement word, sign-extended to 32 bytes
+pub(crate) fn parse_int(value: &str, bits: usize) -> Result<[u8; 32]> {
+    let (negative, magnitude) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let mut word = parse_uint(magnitude, bits)?;
+
+    // Magnitude must fit in bits - 1, except for the minimum value (-2^(bits-1))
+    let sign_byte = 32 - bits / 8;
+    let sign_bit_set = word[sign_byte] & 0x80 != 0;
+    let is_min =
+        sign_bit_set && word[sign_byte] == 0x80 && word[sign_byte + 1..].iter().all(|b| *b == 0);
+    anyhow::ensure!(
+        !sign_bit_set || (negative && is_min),
+        "Integer key does not fit in int{}: {}",
+        bits,
+        value
+    );
+
+    if negative {
+        // Two's complement: invert and add one
+        let mut carry = true;
+        for byte in word.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                let (sum, overflow) = byte.overflowing_add(1);
+                *byte = sum;
+                carry = overflow;
+            }
+        }
+    }
+    Ok(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_type_keys_are_padded() -> Result<()> {
+        let uint: MappingKey = "uint256:42".parse()?;
+        assert_eq!(uint.encode()[31], 42);
+        assert_eq!(uint.encode().len(), 32);
+
+        let neg: MappingKey = "int8:-1".parse()?;
+        assert_eq!(neg.encode(), vec![0xff; 32]);
+
+        let flag: MappingKey = "bool:true".parse()?;
+        assert_eq!(flag.encode()[31], 1);
+
+        let sig: MappingKey = "bytes4:0xa9059cbb".parse()?;
+        assert_eq!(&sig.encode()[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(sig.encode()[4..].iter().all(|b| *b == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dynamic_keys_are_unpadded() -> Result<()> {
+        let name: MappingKey = "string:USDC".parse()?;
+        assert_eq!(name.encode(), b"USDC".to_vec());
+
+        let raw: MappingKey = "bytes:0xdead".parse()?;
+        assert_eq!(raw.encode(), vec![0xde, 0xad]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_keys() {
+        assert!("uint8:256".parse::<MappingKey>().is_err());
+        assert!("int8:128".parse::<MappingKey>().is_err());
+        assert!("int8:-128".parse::<MappingKey>().is_ok());
+        assert!("bytes4:0xdeadbeef00".parse::<MappingKey>().is_err());
+        assert!("0x1234".parse::<MappingKey>().is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use crate::decode::{DecodedValue, ValueType, decode_field, decode_string, parse_word};
+use crate::key::MappingKey;
+use crate::read_bytes_at;
+use crate::rpc::{BlockId, get_storage_at};
+use crate::slot::Slot;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Compiler `storageLayout` output (`solc --storage-layout`, `forge inspect <C> storageLayout`)
+#[derive(Deserialize, Debug, Clone)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageEntry>,
+    #[serde(default)]
+    pub types: Option<HashMap<String, TypeInfo>>,
+}
+
+/// A state variable, or a struct member when nested in `TypeInfo::members`
+#[derive(Deserialize, Debug, Clone)]
+pub struct StorageEntry {
+    pub label: String,
+    pub offset: usize,
+    pub slot: String,
+    #[serde(rename = "type")]
+    pub type_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeInfo {
+    /// `inplace`, `mapping`, `dynamic_array` or `bytes`
+    pub encoding: String,
+    pub label: String,
+    pub number_of_bytes: String,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub base: Option<String>,
+    pub members: Option<Vec<StorageEntry>>,
+}
+
+/// What sits at a resolved location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableKind {
+    Value(ValueType),
+    String,
+    Bytes,
+    /// `T[]`, the slot holds the length
+    DynamicArray,
+    Mapping,
+    Struct,
+    StaticArray,
+}
+
+/// A path resolved to its exact location in storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedVariable {
+    pub slot: Slot,
+    /// Bytes from the low-order end of the slot
+    pub offset: usize,
+    /// Type label as written in the source (`mapping(address => uint256)`)
+    pub label: String,
+    pub kind: VariableKind,
+}
+
+impl StorageLayout {
+    /// Accepts the bare layout or an object wrapping it under `storageLayout`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).context("Invalid storage layout JSON")?;
+        let layout = match value.get("storageLayout") {
+            Some(inner) => inner.clone(),
+            None => value,
+        };
+        serde_json::from_value(layout).context("Unexpected storage layout format")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_json(&json)
+    }
+
+    fn type_info(&self, type_id: &str) -> Result<&TypeInfo> {
+        self.types
+            .as_ref()
+            .and_then(|types| types.get(type_id))
+            .with_context(|| format!("Type {} missing from layout", type_id))
+    }
+
+    /// Resolve `balanceOf[0xabc]`, `positions[7].liquidity`, `_owners[42]`, `matrix[1][2]`
+    pub fn resolve(&self, path: &str) -> Result<ResolvedVariable> {
+        let segments = parse_path(path)?;
+        let mut segments = segments.into_iter();
+
+        let Some(Segment::Field(root)) = segments.next() else {
+            anyhow::bail!("Path must start with a variable name: {}", path);
+        };
+        let entry = self
+            .storage
+            .iter()
+            .find(|e| e.label == root)
+            .with_context(|| format!("No state variable named {}", root))?;
+
+        let mut slot: Slot = entry.slot.parse()?;
+        let mut offset = entry.offset;
+        let mut type_id = entry.type_id.clone();
+
+        for segment in segments {
+            let info = self.type_info(&type_id)?;
+            match segment {
+                Segment::Field(name) => {
+                    let member = info
+                        .members
+                        .as_ref()
+                        .and_then(|members| members.iter().find(|m| m.label == name))
+                        .with_context(|| format!("{} has no member {}", info.label, name))?;
+                    slot = slot.wrapping_add(&member.slot.parse()?);
+                    offset = member.offset;
+                    type_id = member.type_id.clone();
+                }
+                Segment::Index(raw) => {
+                    (slot, offset, type_id) = self.index_into(info, slot, &raw)?;
+                }
+            }
+        }
+
+        let info = self.type_info(&type_id)?;
+        Ok(ResolvedVariable {
+            slot,
+            offset,
+            label: info.label.clone(),
+            kind: variable_kind(info)?,
+        })
+    }
+
+    /// Variables stored directly in `slot` (state variables and struct members), with their paths.
+    /// Mapping values and array elements cannot be found this way, their slots are hashed.
+    pub fn variables_at(&self, slot: Slot) -> Result<Vec<(String, ResolvedVariable)>> {
+        let mut found = Vec::new();
+        for entry in &self.storage {
+            self.collect_at(slot, Slot::ZERO, entry, &entry.label, &mut found)?;
+        }
+        Ok(found)
+    }
+
+    fn collect_at(
+        &self,
+        target: Slot,
+        base: Slot,
+        entry: &StorageEntry,
+        path: &str,
+        found: &mut Vec<(String, ResolvedVariable)>,
+    ) -> Result<()> {
+        let start = base.wrapping_add(&entry.slot.parse()?);
+        let info = self.type_info(&entry.type_id)?;
+        let words = info.number_of_bytes.parse::<u64>()?.div_ceil(32).max(1);
+        if target < start || target >= start.offset(words) {
+            return Ok(());
+        }
+
+        match &info.members {
+            Some(members) => {
+                for member in members {
+                    let path = format!("{}.{}", path, member.label);
+                    self.collect_at(target, start, member, &path, found)?;
+                }
+            }
+            None if start == target => found.push((
+                path.to_string(),
+                ResolvedVariable {
+                    slot: start,
+                    offset: entry.offset,
+                    label: info.label.clone(),
+                    kind: variable_kind(info)?,
+                },
+            )),
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn index_into(&self, info: &TypeInfo, slot: Slot, raw: &str) -> Result<(Slot, usize, String)> {
+        match info.encoding.as_str() {
+            "mapping" => {
+                let key_id = info.key.as_deref().context("Mapping without key type")?;
+                let key_label = &self.type_info(key_id)?.label;
+                let key: MappingKey = format!("{}:{}", key_type_name(key_label), raw).parse()?;
+                let value = info.value.clone().context("Mapping without value type")?;
+                Ok((slot.mapping(&key), 0, value))
+            }
+            "dynamic_array" | "inplace" if info.base.is_some() => {
+                let base = info.base.clone().context("Array without base type")?;
+                let element_size: u64 = self.type_info(&base)?.number_of_bytes.parse()?;
+                let index: u64 = raw
+                    .parse()
+                    .with_context(|| format!("Invalid array index: {}", raw))?;
+                let (slot, offset) = if info.encoding == "dynamic_array" {
+                    slot.array_element(index, element_size)?
+                } else {
+                    slot.static_array_element(index, element_size)?
+                };
+                Ok((slot, offset, base))
+            }
+            _ => anyhow::bail!("Cannot index into {}", info.label),
+        }
+    }
+}
+
+impl ResolvedVariable {
+    /// Fetch the location and decode it according to its kind
+    pub async fn read(&self, rpc_url: &str, address: &str, block: BlockId) -> Result<DecodedValue> {
+        match &self.kind {
+            VariableKind::Value(ty) => {
+                let raw = get_storage_at(rpc_url, address, &self.slot.to_string(), block).await?;
+                decode_field(&parse_word(&raw)?, self.offset, None, *ty)
+            }
+            VariableKind::String => Ok(decode_string(
+                read_bytes_at(rpc_url, address, self.slot, block).await?,
+            )),
+            VariableKind::Bytes => Ok(DecodedValue::Bytes(
+                read_bytes_at(rpc_url, address, self.slot, block).await?,
+            )),
+            VariableKind::DynamicArray => {
+                let raw = get_storage_at(rpc_url, address, &self.slot.to_string(), block).await?;
+                decode_field(&parse_word(&raw)?, 0, None, ValueType::Uint(256))
+            }
+            _ => anyhow::bail!("{} has no single value, index into it", self.label),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(String),
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = path.trim();
+
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let end = inner
+                .find(']')
+                .with_context(|| format!("Unclosed [ in {}", path))?;
+            let key = inner[..end].trim().trim_matches('"');
+            segments.push(Segment::Index(key.to_string()));
+            rest = &inner[end + 1..];
+        } else {
+            let field = rest.strip_prefix('.').unwrap_or(rest);
+            let end = field.find(['.', '[']).unwrap_or(field.len());
+            anyhow::ensure!(end > 0, "Empty name in {}", path);
+            segments.push(Segment::Field(field[..end].to_string()));
+            rest = &field[end..];
+        }
+    }
+    Ok(segments)
+}
+
+// Solidity label of a mapping key -> MappingKey type prefix
+fn key_type_name(label: &str) -> &str {
+    if label.starts_with("contract ") || label.starts_with("address") {
+        "address"
+    } else if label.starts_with("enum ") {
+        "uint8"
+    } else {
+        label
+    }
+}
+
+fn variable_kind(info: &TypeInfo) -> Result<VariableKind> {
+    let kind = match info.encoding.as_str() {
+        "mapping" => VariableKind::Mapping,
+        "dynamic_array" => VariableKind::DynamicArray,
+        "bytes" if info.label == "string" => VariableKind::String,
+        "bytes" => VariableKind::Bytes,
+        "inplace" if info.members.is_some() => VariableKind::Struct,
+        "inplace" if info.base.is_some() => VariableKind::StaticArray,
+        "inplace" => {
+            let label = info.label.as_str();
+            if label.starts_with("contract ") || label.starts_with("address") {
+                VariableKind::Value(ValueType::Address)
+            } else if label.starts_with("enum ") {
+                VariableKind::Value(ValueType::Uint(8))
+            } else {
+                VariableKind::Value(label.parse()?)
+            }
+        }
+        other => anyhow::bail!("Unknown storage encoding: {}", other),
+    };
+    Ok(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = r#"{
+        "storage": [
+            {"astId": 1, "contract": "T", "label": "name", "offset": 0, "slot": "0", "type": "t_string_storage"},
+            {"astId": 2, "contract": "T", "label": "paused", "offset": 0, "slot": "1", "type": "t_bool"},
+            {"astId": 3, "contract": "T", "label": "owner", "offset": 1, "slot": "1", "type": "t_address"},
+            {"astId": 4, "contract": "T", "label": "balanceOf", "offset": 0, "slot": "3", "type": "t_mapping(t_address,t_uint256)"},
+            {"astId": 5, "contract": "T", "label": "positions", "offset": 0, "slot": "4", "type": "t_mapping(t_uint256,t_struct(Position)9_storage)"},
+            {"astId": 6, "contract": "T", "label": "_owners", "offset": 0, "slot": "5", "type": "t_array(t_address)dyn_storage"}
+        ],
+        "types": {
+            "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+            "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+            "t_uint128": {"encoding": "inplace", "label": "uint128", "numberOfBytes": "16"},
+            "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+            "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+            "t_array(t_address)dyn_storage": {"base": "t_address", "encoding": "dynamic_array", "label": "address[]", "numberOfBytes": "32"},
+            "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+            "t_mapping(t_uint256,t_struct(Position)9_storage)": {"encoding": "mapping", "key": "t_uint256", "label": "mapping(uint256 => struct T.Position)", "numberOfBytes": "32", "value": "t_struct(Position)9_storage"},
+            "t_struct(Position)9_storage": {"encoding": "inplace", "label": "struct T.Position", "numberOfBytes": "64", "members": [
+                {"astId": 7, "contract": "T", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+                {"astId": 8, "contract": "T", "label": "liquidity", "offset": 0, "slot": "1", "type": "t_uint128"}
+            ]}
+        }
+    }"#;
+
+    #[test]
+    fn test_resolve_mapping_matches_derivation() -> Result<()> {
+        let layout = StorageLayout::from_json(LAYOUT)?;
+        let var = layout.resolve("balanceOf[0xF977814e90dA44bFA03b6295A0616a897441aceC]")?;
+        assert_eq!(
+            var.slot.to_string(),
+            "0x9cca97fb08ee88532e0983a3a051466c5df908292b6899f3cdc163eb9c0b22ba"
+        );
+        assert_eq!(var.kind, VariableKind::Value(ValueType::Uint(256)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_struct_member_and_array() -> Result<()> {
+        let layout = StorageLayout::from_json(LAYOUT)?;
+
+        let liquidity = layout.resolve("positions[42].liquidity")?;
+        let key: MappingKey = "uint256:42".parse()?;
+        assert_eq!(liquidity.slot, Slot::from(4).mapping(&key).offset(1));
+        assert_eq!(liquidity.kind, VariableKind::Value(ValueType::Uint(128)));
+
+        let owner = layout.resolve("_owners[3]")?;
+        assert_eq!(owner.slot, Slot::from(5).hashed().offset(3));
+
+        let packed = layout.resolve("owner")?;
+        assert_eq!((packed.slot, packed.offset), (Slot::from(1), 1));
+
+        assert_eq!(layout.resolve("name")?.kind, VariableKind::String);
+        assert!(layout.resolve("positions[42].missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_variables_at_packed_slot() -> Result<()> {
+        let layout = StorageLayout::from_json(LAYOUT)?;
+
+        let found = layout.variables_at(Slot::from(1))?;
+        let labels: Vec<(&str, usize)> = found
+            .iter()
+            .map(|(path, var)| (path.as_str(), var.offset))
+            .collect();
+        assert_eq!(labels, vec![("paused", 0), ("owner", 1)]);
+
+        assert!(layout.variables_at(Slot::from(2))?.is_empty());
+        Ok(())
+    }
+}
@@ -1,63 +1,154 @@
-use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
-use sha3::{Digest, Keccak256};
-
-#[derive(Serialize)]
-struct JsonRpcRequest {
-    jsonrpc: String,
-    method: String,
-    params: serde_json::Value,
-    id: u32,
-}
+use anyhow::Result;
 
-#[derive(Deserialize, Debug)]
-struct JsonRpcResponse {
-    result: String,
-}
+mod abi;
+mod batch;
+mod bytecode;
+mod decode;
+mod diff;
+mod discover;
+mod dump;
+mod history;
+mod holders;
+mod key;
+mod layout;
+mod namespace;
+mod proof;
+mod proxy;
+mod quorum;
+mod rlp;
+mod rpc;
+mod simulate;
+mod slot;
+mod storage_range;
+mod trace;
+mod watch;
+mod write;
 
-pub async fn get_storage_at(rpc_url: &str, address: &str, slot: &str) -> Result<String> {
-    let client = reqwest::Client::new();
+pub use abi::{AbiType, Signature};
+pub use batch::{BatchOptions, StorageRequest, get_storage_batch};
+pub use bytecode::{
+    Instruction, SlotCandidate, SlotPattern, analyze_bytecode, disassemble, get_code,
+};
+pub use decode::{
+    BytesLayout, DecodedValue, ValueType, decode_field, decode_string, extract_field, parse_word,
+};
+pub use diff::{SlotDiff, diff_ranges, diff_slots, diff_storage};
+pub use discover::{
+    BalanceSlot, Discovery, access_list_slots, balance_of, candidate_slots, discover_balance_slot,
+};
+pub use dump::{DumpEntry, PreimageCandidates, SlotOrigin, annotate_dump};
+pub use history::{SlotChange, storage_history};
+pub use holders::{HolderBalance, holder_balances, parse_holders};
+pub use key::MappingKey;
+use key::parse_address;
+pub use layout::{ResolvedVariable, StorageEntry, StorageLayout, TypeInfo, VariableKind};
+pub use namespace::{Namespace, erc7201_slot};
+pub use proof::{
+    Account, EMPTY_TRIE_ROOT, ProofResponse, StorageProof, VerifiedSlot, get_proof, get_state_root,
+    get_verified_storage, verify_storage_proof, verify_trie_proof,
+};
+pub use proxy::{
+    EIP1822_PROXIABLE_SLOT, EIP1967_ADMIN_SLOT, EIP1967_BEACON_SLOT, EIP1967_IMPLEMENTATION_SLOT,
+    ProxyInfo, ProxyKind, ZEPPELINOS_ADMIN_SLOT, ZEPPELINOS_IMPLEMENTATION_SLOT, address_from_word,
+    detect_proxy, eip1967_slot,
+};
+pub use quorum::{ProviderAnswer, QuorumRead, provider_label, quorum_storage_at};
+pub use rlp::Rlp;
+pub use rpc::{BlockId, RpcError, block_number, eth_call, get_storage_at, resolve_block};
+pub use simulate::{StateOverride, eth_call_with_overrides};
+pub use slot::{Compiler, Slot, element_location};
+pub use storage_range::{RangeEntry, block_hash, storage_dump, storage_range_at};
+pub use trace::{AccountChanges, trace_storage_changes, transaction_addresses};
+pub use watch::{Threshold, delta, field_value, parse_limit};
+pub use write::{DevNode, detect_dev_node, set_storage_at, write_storage};
 
-    let payload = JsonRpcRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "eth_getStorageAt".to_string(),
-        params: serde_json::json!([address, slot, "latest"]),
-        id: 1,
-    };
+// Read a Solidity `string` / `bytes` variable whose head is at `slot`
+// short: data + len*2 in one slot, long: len*2+1 then data at keccak256(slot)
+pub async fn read_bytes_at(
+    rpc_url: &str,
+    address: &str,
+    slot: Slot,
+    block: BlockId,
+) -> Result<Vec<u8>> {
+    read_bytes_at_for(rpc_url, address, slot, block, Compiler::Solidity).await
+}
 
-    let response = client
-        .post(rpc_url)
-        .json(&payload)
-        .send()
-        .await
-        .context("Failed to send RPC request")?;
+// Same as `read_bytes_at` for either compiler
+// Vyper `String[N]` / `Bytes[N]`: plain length at slot, data from slot + 1
+pub async fn read_bytes_at_for(
+    rpc_url: &str,
+    address: &str,
+    slot: Slot,
+    block: BlockId,
+    compiler: Compiler,
+) -> Result<Vec<u8>> {
+    let head = parse_word(&get_storage_at(rpc_url, address, &slot.to_string(), block).await?)?;
 
-    let parsed: JsonRpcResponse = response
-        .json()
-        .await
-        .context("Failed to parse RPC response")?;
+    let (len, data_start) = match compiler {
+        Compiler::Solidity => match BytesLayout::from_word(&head)? {
+            BytesLayout::Short(data) => return Ok(data),
+            BytesLayout::Long(len) => (len, slot.hashed()),
+        },
+        Compiler::Vyper => {
+            anyhow::ensure!(
+                head[..24].iter().all(|b| *b == 0),
+                "Vyper length does not fit in 64 bits"
+            );
+            let mut raw = [0u8; 8];
+            raw.copy_from_slice(&head[24..]);
+            (usize::try_from(u64::from_be_bytes(raw))?, slot.offset(1))
+        }
+    };
 
-    Ok(parsed.result)
+    let data_slots = len.div_ceil(32);
+    let mut data = Vec::with_capacity(data_slots * 32);
+    for i in 0..data_slots as u64 {
+        let chunk =
+            get_storage_at(rpc_url, address, &data_start.offset(i).to_string(), block).await?;
+        data.extend_from_slice(&parse_word(&chunk)?);
+    }
+    data.truncate(len);
+    Ok(data)
 }
 
 // Find the storage slot for an address in a Solidity mapping
 // keccak256(h(k) + p) k -> address, p -> slot position
-pub fn derive_mapping_slot(user_address: &str, mapping_slot: u64) -> Result<String> {
-    let addr_hex = user_address.trim_start_matches("0x");
-    let addr_bytes = hex::decode(addr_hex).context("Failed to decode user address")?;
+pub fn derive_mapping_slot(user_address: &str, mapping_slot: impl Into<Slot>) -> Result<String> {
+    derive_mapping_slot_for(user_address, mapping_slot, Compiler::Solidity)
+}
 
-    let mut buffer = [0u8; 64];
+// Same as `derive_mapping_slot` for either compiler, Vyper hashes keccak256(p + h(k))
+pub fn derive_mapping_slot_for(
+    user_address: &str,
+    mapping_slot: impl Into<Slot>,
+    compiler: Compiler,
+) -> Result<String> {
+    let key = MappingKey::Address(parse_address(user_address)?);
+    Ok(derive_slot_path_for(mapping_slot.into(), &[key], compiler).to_string())
+}
 
-    // 20 bytes address
-    buffer[12..32].copy_from_slice(&addr_bytes);
-    // 8 bytes slot index
-    buffer[56..64].copy_from_slice(&mapping_slot.to_be_bytes());
+// Find the storage slot behind a chain of nested mappings
+// mapping(k1 => mapping(k2 => v)) -> keccak256(h(k2) + keccak256(h(k1) + p))
+pub fn derive_slot_path(base_slot: Slot, keys: &[MappingKey]) -> Slot {
+    derive_slot_path_for(base_slot, keys, Compiler::Solidity)
+}
 
-    let mut hasher = Keccak256::new();
-    hasher.update(buffer);
-    let result = hasher.finalize();
+// Same as `derive_slot_path` with the compiler's hashing order
+// Vyper HashMap[k1, HashMap[k2, v]] -> keccak256(keccak256(p + h(k1)) + h(k2))
+pub fn derive_slot_path_for(base_slot: Slot, keys: &[MappingKey], compiler: Compiler) -> Slot {
+    keys.iter()
+        .fold(base_slot, |slot, key| slot.mapping_for(key, compiler))
+}
 
-    Ok(format!("0x{}", hex::encode(result)))
+// Find a slot inside a namespaced storage struct: member `member` of the struct at
+// the namespace root, then the mapping keys, e.g. OZ v5 ERC20Storage._balances[k]
+pub fn derive_namespaced_slot(
+    namespace: &Namespace,
+    member: u64,
+    keys: &[MappingKey],
+    compiler: Compiler,
+) -> Slot {
+    derive_slot_path_for(namespace.root().offset(member), keys, compiler)
 }
 
 #[cfg(test)]
@@ -75,4 +166,53 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_slot_path_single_key_matches_mapping() -> Result<()> {
+        let holder = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
+        assert_eq!(
+            derive_slot_path(Slot::from(3), &[holder.parse()?]).to_string(),
+            derive_mapping_slot(holder, 3)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_slot_path_nested_allowance() -> Result<()> {
+        // allowance[owner][spender] at slot 4 (WETH)
+        let owner: MappingKey = "0xF977814e90dA44bFA03b6295A0616a897441aceC".parse()?;
+        let spender: MappingKey = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".parse()?;
+
+        let expected = Slot::from(4).mapping(&owner).mapping(&spender);
+        assert_eq!(derive_slot_path(Slot::from(4), &[owner, spender]), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_slot_path_zero_key_slot_zero() {
+        // keccak256(64 zero bytes), same as mapping(uint256 => _)[0] at slot 0
+        let zero = MappingKey::Uint([0u8; 32]);
+        assert_eq!(
+            derive_slot_path(Slot::ZERO, &[zero]).to_string(),
+            "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+        );
+    }
+
+    #[test]
+    fn test_slot_path_for_vyper_nests_slot_first() {
+        let owner = MappingKey::Address([0x11; 20]);
+        let spender = MappingKey::Address([0x22; 20]);
+        let expected = Slot::from(3)
+            .mapping_for(&owner, Compiler::Vyper)
+            .mapping_for(&spender, Compiler::Vyper);
+        assert_eq!(
+            derive_slot_path_for(
+                Slot::from(3),
+                &[owner.clone(), spender.clone()],
+                Compiler::Vyper
+            ),
+            expected
+        );
+        assert_ne!(expected, derive_slot_path(Slot::from(3), &[owner, spender]));
+    }
 }
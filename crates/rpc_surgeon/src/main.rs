@@ -1,50 +1,105 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use rpc_surgeon::{derive_mapping_slot, get_storage_at};
+use clap::{Parser, Subcommand};
+use rpc_surgeon::RpcError;
+
+mod commands;
+
+use commands::balances::BalancesArgs;
+use commands::call::CallArgs;
+use commands::diff::DiffArgs;
+use commands::discover::DiscoverArgs;
+use commands::dump::DumpArgs;
+use commands::explain::ExplainArgs;
+use commands::infer::InferArgs;
+use commands::namespace::NamespaceArgs;
+use commands::proxy::ProxyArgs;
+use commands::read::ReadArgs;
+use commands::shell::ShellArgs;
+use commands::watch::WatchArgs;
+use commands::write::WriteArgs;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
-struct Args {
-    #[arg(short, long)]
-    contract: String,
+#[command(subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    #[arg(short, long)]
-    owner: String,
+    /// Default mode: read a single variable
+    #[command(flatten)]
+    read: Option<ReadArgs>,
 
-    #[arg(short, long)]
-    slot: u64,
-
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     rpc: Option<String>,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Read a single variable (same as running without a subcommand)
+    Read(Box<ReadArgs>),
+    /// Resolve a proxy's implementation and admin from the standard slots
+    Proxy(ProxyArgs),
+    /// Find an ERC-20's balance mapping slot from a known holder
+    Discover(DiscoverArgs),
+    /// Snapshot many holders' balances from a list of addresses
+    Balances(BalancesArgs),
+    /// Report the storage slots that changed between two blocks
+    Diff(DiffArgs),
+    /// Dump every storage slot as JSON, reversing hashed slots where possible
+    Dump(DumpArgs),
+    /// Overwrite a (derived) slot on an anvil / hardhat node and read it back
+    Write(WriteArgs),
+    /// eth_call a view function on top of hypothetical storage
+    Call(CallArgs),
+    /// Re-read a slot on every new block and alert on changes
+    Watch(WatchArgs),
+    /// Guess storage slots from the bytecode when there is no source
+    Infer(InferArgs),
+    /// List the slots a transaction changed and whose mapping entries they are
+    Explain(ExplainArgs),
+    /// Compute an ERC-7201 / EIP-1967 namespaced storage root and the slots under it (offline)
+    Namespace(NamespaceArgs),
+    /// Interactive shell keeping a contract, block and compiler between reads
+    Shell(ShellArgs),
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let cli = Cli::parse();
 
     dotenvy::dotenv().ok();
-    let rpc_url = args
-        .rpc
-        .or_else(|| std::env::var("RPC_URL").ok())
-        .context("RPC_URL must be provided via --rpc or .env file")?;
-
-    // Exemple:
-    // eth_contract = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
-    // binance_holder = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
-    // mapping_slot = 3;
-
-    let target_slot = derive_mapping_slot(&args.owner, args.slot)?;
-
-    println!("--- SURGERY RESULT ---");
-    println!("Target Slot: {}", target_slot);
+    let rpc_url = cli.rpc.or_else(|| std::env::var("RPC_URL").ok());
+    // Pure slot arithmetic runs without a node
+    let rpc_url = match (&cli.command, rpc_url) {
+        (Some(Command::Namespace(_)), rpc_url) => rpc_url.unwrap_or_default(),
+        (_, rpc_url) => rpc_url.context("RPC_URL must be provided via --rpc or .env file")?,
+    };
 
-    let raw_balance = get_storage_at(&rpc_url, &args.contract, &target_slot).await?;
-    println!("Raw value: {}", raw_balance);
+    let result = match cli.command {
+        Some(Command::Read(args)) => commands::read::run(*args, &rpc_url).await,
+        Some(Command::Proxy(args)) => commands::proxy::run(args, &rpc_url).await,
+        Some(Command::Discover(args)) => commands::discover::run(args, &rpc_url).await,
+        Some(Command::Balances(args)) => commands::balances::run(args, &rpc_url).await,
+        Some(Command::Diff(args)) => commands::diff::run(args, &rpc_url).await,
+        Some(Command::Dump(args)) => commands::dump::run(args, &rpc_url).await,
+        Some(Command::Write(args)) => commands::write::run(args, &rpc_url).await,
+        Some(Command::Call(args)) => commands::call::run(args, &rpc_url).await,
+        Some(Command::Watch(args)) => commands::watch::run(args, &rpc_url).await,
+        Some(Command::Infer(args)) => commands::infer::run(args, &rpc_url).await,
+        Some(Command::Explain(args)) => commands::explain::run(args, &rpc_url).await,
+        Some(Command::Namespace(args)) => commands::namespace::run(args),
+        Some(Command::Shell(args)) => commands::shell::run(args, &rpc_url).await,
+        None => {
+            let args = cli.read.context("--contract and --slot are required")?;
+            commands::read::run(args, &rpc_url).await
+        }
+    };
 
-    let clean = raw_balance.trim_start_matches("0x");
-    if !clean.is_empty() && clean != "0" {
-        let val = u128::from_str_radix(clean, 16).unwrap_or(0);
-        println!("Decimal value: {}", val);
+    if let Err(e) = &result
+        && e.downcast_ref::<RpcError>()
+            .is_some_and(RpcError::is_missing_state)
+    {
+        eprintln!("Hint: the node has pruned this block's state, retry with an archive RPC");
     }
-    Ok(())
+    result
 }
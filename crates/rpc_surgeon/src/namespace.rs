@@ -0,0 +1,90 @@
+use crate::proxy::eip1967_slot;
+use crate::slot::{Slot, keccak256};
+use anyhow::Result;
+use std::fmt;
+use std::str::FromStr;
+
+/// A storage struct placed at a slot derived from a namespace id instead of slot 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Namespace {
+    /// ERC-7201 `@custom:storage-location erc7201:<id>` (OpenZeppelin v5)
+    Erc7201(String),
+    /// keccak256(id) - 1, as EIP-1967 proxy slots
+    Eip1967(String),
+}
+
+impl Namespace {
+    /// Slot of the struct's first member
+    pub fn root(&self) -> Slot {
+        match self {
+            Namespace::Erc7201(id) => erc7201_slot(id),
+            Namespace::Eip1967(id) => eip1967_slot(id),
+        }
+    }
+}
+
+/// `erc7201:<id>`, `eip1967:<id>`, or a bare ERC-7201 id
+impl FromStr for Namespace {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let namespace = match s.split_once(':') {
+            Some(("erc7201", id)) => Namespace::Erc7201(id.to_string()),
+            Some(("eip1967", id)) => Namespace::Eip1967(id.to_string()),
+            _ => Namespace::Erc7201(s.to_string()),
+        };
+        match &namespace {
+            Namespace::Erc7201(id) | Namespace::Eip1967(id) => {
+                anyhow::ensure!(!id.is_empty(), "Empty namespace id: {}", s)
+            }
+        }
+        Ok(namespace)
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Namespace::Erc7201(id) => write!(f, "erc7201:{}", id),
+            Namespace::Eip1967(id) => write!(f, "eip1967:{}", id),
+        }
+    }
+}
+
+/// keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~0xff
+/// The low byte is cleared so the struct's first 256 slots share the same prefix
+pub fn erc7201_slot(id: &str) -> Slot {
+    let inner = Slot(keccak256(id.as_bytes())).wrapping_sub(&Slot::from(1));
+    let mut root = keccak256(&inner.0);
+    root[31] = 0;
+    Slot(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openzeppelin_v5_roots() -> Result<()> {
+        assert_eq!(
+            erc7201_slot("openzeppelin.storage.ERC20").to_string(),
+            "0x52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00"
+        );
+        assert_eq!(
+            "openzeppelin.storage.Ownable"
+                .parse::<Namespace>()?
+                .root()
+                .to_string(),
+            "0x9016d09d72d40fdae2fd8ceac6b6234c7706214fd39c1cd1e609a0528c199300"
+        );
+        assert_eq!(
+            "eip1967:eip1967.proxy.implementation"
+                .parse::<Namespace>()?
+                .root()
+                .to_string(),
+            "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc"
+        );
+        assert!("erc7201:".parse::<Namespace>().is_err());
+        Ok(())
+    }
+}
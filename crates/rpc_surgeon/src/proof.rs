@@ -0,0 +1,390 @@
+use crate::decode::parse_word;
+use crate::key::parse_address;
+use crate::rlp::{self, Rlp};
+use crate::rpc::{BlockId, call};
+use crate::slot::{Slot, keccak256};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// keccak256(rlp("")), root of an empty trie
+pub const EMPTY_TRIE_ROOT: [u8; 32] = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+/// `eth_getProof` result
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofResponse {
+    pub address: String,
+    pub account_proof: Vec<String>,
+    pub balance: String,
+    pub code_hash: String,
+    pub nonce: String,
+    pub storage_hash: String,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StorageProof {
+    pub key: String,
+    pub value: String,
+    pub proof: Vec<String>,
+}
+
+/// Account fields proven against the state root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub nonce: Vec<u8>,
+    pub balance: Vec<u8>,
+    pub storage_root: [u8; 32],
+    pub code_hash: [u8; 32],
+}
+
+/// A storage word whose value was checked against a state root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedSlot {
+    /// `None` when the proof shows the account does not exist
+    pub account: Option<Account>,
+    pub value: [u8; 32],
+}
+
+enum NodeRef {
+    Hash([u8; 32]),
+    Inline(Rlp),
+    Empty,
+}
+
+/// Walk a secure Merkle-Patricia proof for `key` (hashed with keccak256 to get the path).
+/// Returns the stored value, or `None` if the proof shows the key is absent.
+pub fn verify_trie_proof(
+    root: &[u8; 32],
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>> {
+    if *root == EMPTY_TRIE_ROOT {
+        return Ok(None);
+    }
+
+    let path = nibbles(&keccak256(key));
+    let mut depth = 0;
+    let mut nodes = proof.iter();
+    let mut next = NodeRef::Hash(*root);
+
+    loop {
+        let node = match next {
+            NodeRef::Empty => return Ok(None),
+            NodeRef::Inline(node) => node,
+            NodeRef::Hash(hash) => {
+                let raw = nodes
+                    .next()
+                    .context("Proof ended before reaching the key")?;
+                anyhow::ensure!(
+                    keccak256(raw) == hash,
+                    "Proof node at depth {} does not match its parent hash",
+                    depth
+                );
+                rlp::decode(raw)?
+            }
+        };
+
+        let items = node.as_list()?;
+        match items.len() {
+            // Branch: 16 children + value
+            17 => {
+                let Some(nibble) = path.get(depth) else {
+                    let value = items[16].as_bytes()?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                };
+                next = node_ref(&items[usize::from(*nibble)])?;
+                depth += 1;
+            }
+            // Extension or leaf
+            2 => {
+                let (partial, is_leaf) = decode_hex_prefix(items[0].as_bytes()?)?;
+                let remaining = &path[depth..];
+                if is_leaf {
+                    if remaining != partial.as_slice() {
+                        return Ok(None);
+                    }
+                    return Ok(Some(items[1].as_bytes()?.to_vec()));
+                }
+                if !remaining.starts_with(&partial) {
+                    return Ok(None);
+                }
+                depth += partial.len();
+                next = node_ref(&items[1])?;
+            }
+            n => anyhow::bail!("Invalid trie node with {} items", n),
+        }
+    }
+}
+
+/// Check an `eth_getProof` response for `slot` against `state_root` and return the proven word.
+/// Fails if any proof is invalid or if the RPC's reported values disagree with the proofs.
+pub fn verify_storage_proof(
+    state_root: &[u8; 32],
+    address: &str,
+    slot: Slot,
+    response: &ProofResponse,
+) -> Result<VerifiedSlot> {
+    let account_proof = decode_proof(&response.account_proof)?;
+    let account_rlp = verify_trie_proof(state_root, &parse_address(address)?, &account_proof)
+        .context("Invalid account proof")?;
+
+    let storage = response
+        .storage_proof
+        .iter()
+        .find(|p| p.key.parse::<Slot>().is_ok_and(|key| key == slot))
+        .context("Slot missing from eth_getProof response")?;
+    let reported = parse_word(&storage.value)?;
+
+    let Some(account_rlp) = account_rlp else {
+        anyhow::ensure!(
+            reported == [0u8; 32],
+            "Account does not exist but RPC reported a non-zero value"
+        );
+        return Ok(VerifiedSlot {
+            account: None,
+            value: [0u8; 32],
+        });
+    };
+    let account = decode_account(&account_rlp)?;
+    anyhow::ensure!(
+        parse_word(&response.storage_hash)? == account.storage_root,
+        "RPC storageHash does not match the proven account"
+    );
+
+    let storage_proof = decode_proof(&storage.proof)?;
+    let value = match verify_trie_proof(&account.storage_root, slot.as_bytes(), &storage_proof)
+        .context("Invalid storage proof")?
+    {
+        Some(encoded) => {
+            let item = rlp::decode(&encoded)?;
+            let bytes = item.as_bytes()?;
+            anyhow::ensure!(bytes.len() <= 32, "Storage value longer than 32 bytes");
+            let mut word = [0u8; 32];
+            word[32 - bytes.len()..].copy_from_slice(bytes);
+            word
+        }
+        None => [0u8; 32],
+    };
+    anyhow::ensure!(
+        value == reported,
+        "RPC reported 0x{} but the proof holds 0x{}",
+        hex::encode(reported),
+        hex::encode(value)
+    );
+
+    Ok(VerifiedSlot {
+        account: Some(account),
+        value,
+    })
+}
+
+pub async fn get_proof(
+    rpc_url: &str,
+    address: &str,
+    slot: Slot,
+    block: BlockId,
+) -> Result<ProofResponse> {
+    Ok(call(
+        rpc_url,
+        "eth_getProof",
+        serde_json::json!([address, [slot.to_string()], block.to_param()]),
+    )
+    .await?)
+}
+
+/// `stateRoot` of a block header, as reported by the RPC
+pub async fn get_state_root(rpc_url: &str, block: BlockId) -> Result<[u8; 32]> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Header {
+        state_root: String,
+    }
+
+    let header: Option<Header> = match block {
+        BlockId::Hash(hash) => {
+            let hash = format!("0x{}", hex::encode(hash));
+            call(
+                rpc_url,
+                "eth_getBlockByHash",
+                serde_json::json!([hash, false]),
+            )
+            .await?
+        }
+        _ => {
+            let params = serde_json::json!([block.to_param(), false]);
+            call(rpc_url, "eth_getBlockByNumber", params).await?
+        }
+    };
+    parse_word(&header.context("Block not found")?.state_root)
+}
+
+/// Fetch `eth_getProof` and verify it against `state_root`
+pub async fn get_verified_storage(
+    rpc_url: &str,
+    address: &str,
+    slot: Slot,
+    block: BlockId,
+    state_root: &[u8; 32],
+) -> Result<VerifiedSlot> {
+    let response = get_proof(rpc_url, address, slot, block).await?;
+    verify_storage_proof(state_root, address, slot, &response)
+}
+
+fn decode_proof(nodes: &[String]) -> Result<Vec<Vec<u8>>> {
+    nodes
+        .iter()
+        .map(|node| hex::decode(node.trim_start_matches("0x")).context("Invalid proof node hex"))
+        .collect()
+}
+
+// RLP([nonce, balance, storageRoot, codeHash])
+fn decode_account(encoded: &[u8]) -> Result<Account> {
+    let item = rlp::decode(encoded)?;
+    let fields = item.as_list()?;
+    anyhow::ensure!(fields.len() == 4, "Account must have 4 fields");
+
+    let hash_field = |item: &Rlp| -> Result<[u8; 32]> {
+        item.as_bytes()?
+            .try_into()
+            .context("Account hash field must be 32 bytes")
+    };
+    Ok(Account {
+        nonce: fields[0].as_bytes()?.to_vec(),
+        balance: fields[1].as_bytes()?.to_vec(),
+        storage_root: hash_field(&fields[2])?,
+        code_hash: hash_field(&fields[3])?,
+    })
+}
+
+fn node_ref(item: &Rlp) -> Result<NodeRef> {
+    match item {
+        Rlp::List(_) => Ok(NodeRef::Inline(item.clone())),
+        Rlp::Bytes(bytes) if bytes.is_empty() => Ok(NodeRef::Empty),
+        Rlp::Bytes(bytes) => bytes
+            .as_slice()
+            .try_into()
+            .map(NodeRef::Hash)
+            .context("Child reference must be a 32-byte hash"),
+    }
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+// Compact (hex-prefix) path encoding: flag nibble 0/1 extension, 2/3 leaf, odd flag adds a nibble
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let first = *encoded.first().context("Empty trie node path")?;
+    let flag = first >> 4;
+    anyhow::ensure!(flag <= 3, "Invalid hex-prefix flag {}", flag);
+
+    let mut path = Vec::new();
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    path.extend(nibbles(&encoded[1..]));
+    Ok((path, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rlp::tests::encode;
+
+    fn bytes(data: &[u8]) -> Rlp {
+        Rlp::Bytes(data.to_vec())
+    }
+
+    // Leaf node for the path remaining after `skip` nibbles of keccak256(key)
+    fn leaf(key: &[u8], skip: usize, value: &[u8]) -> Vec<u8> {
+        let path = &nibbles(&keccak256(key))[skip..];
+        let mut hp = if path.len() % 2 == 1 {
+            vec![0x30 | path[0]]
+        } else {
+            vec![0x20]
+        };
+        let rest = &path[path.len() % 2..];
+        hp.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+        encode(&Rlp::List(vec![bytes(&hp), bytes(value)]))
+    }
+
+    // Storage trie with slot 0 = 0x2a and slot 1 = 0x0100, both under a root branch
+    fn storage_trie() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let (slot0, slot1) = (Slot::from(0), Slot::from(1));
+        let leaf0 = leaf(slot0.as_bytes(), 1, &encode(&bytes(&[0x2a])));
+        let leaf1 = leaf(slot1.as_bytes(), 1, &encode(&bytes(&[0x01, 0x00])));
+
+        // keccak256(slot 0) starts with 0x2, keccak256(slot 1) with 0xb
+        let mut children = vec![bytes(&[]); 17];
+        children[0x2] = bytes(&keccak256(&leaf0));
+        children[0xb] = bytes(&keccak256(&leaf1));
+        (encode(&Rlp::List(children)), leaf0, leaf1)
+    }
+
+    #[test]
+    fn test_trie_proof_inclusion_and_absence() -> Result<()> {
+        let (branch, leaf0, _) = storage_trie();
+        let root = keccak256(&branch);
+
+        let proof = vec![branch.clone(), leaf0.clone()];
+        let value = verify_trie_proof(&root, Slot::from(0).as_bytes(), &proof)?;
+        assert_eq!(value, Some(vec![0x2a]));
+
+        // keccak256(slot 2) starts with 0x4, an empty branch child
+        let absent = verify_trie_proof(
+            &root,
+            Slot::from(2).as_bytes(),
+            std::slice::from_ref(&branch),
+        )?;
+        assert_eq!(absent, None);
+
+        let mut tampered = leaf0;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(verify_trie_proof(&root, Slot::from(0).as_bytes(), &[branch, tampered]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_storage_proof_end_to_end() -> Result<()> {
+        let (branch, _, leaf1) = storage_trie();
+        let storage_root = keccak256(&branch);
+
+        let address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        let account = encode(&Rlp::List(vec![
+            bytes(&[0x01]),
+            bytes(&[0x0d, 0xe0, 0xb6]),
+            bytes(&storage_root),
+            bytes(&keccak256(&[])),
+        ]));
+        let account_leaf = leaf(&parse_address(address)?, 0, &account);
+        let state_root = keccak256(&account_leaf);
+
+        let to_hex = |node: &Vec<u8>| format!("0x{}", hex::encode(node));
+        let mut response = ProofResponse {
+            address: address.to_string(),
+            account_proof: vec![to_hex(&account_leaf)],
+            balance: "0xde0b6".to_string(),
+            code_hash: to_hex(&keccak256(&[]).to_vec()),
+            nonce: "0x1".to_string(),
+            storage_hash: to_hex(&storage_root.to_vec()),
+            storage_proof: vec![StorageProof {
+                key: "0x1".to_string(),
+                value: "0x100".to_string(),
+                proof: vec![to_hex(&branch), to_hex(&leaf1)],
+            }],
+        };
+
+        let verified = verify_storage_proof(&state_root, address, Slot::from(1), &response)?;
+        assert_eq!(verified.value, parse_word("0x100")?);
+
+        // A lying RPC is caught
+        response.storage_proof[0].value = "0x200".to_string();
+        assert!(verify_storage_proof(&state_root, address, Slot::from(1), &response).is_err());
+        Ok(())
+    }
+}
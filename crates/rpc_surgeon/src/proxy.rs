@@ -0,0 +1,148 @@
+use crate::decode::parse_word;
+use crate::rpc::{BlockId, eth_call, get_storage_at};
+use crate::slot::{Slot, keccak256};
+use anyhow::Result;
+use std::fmt;
+
+/// keccak256("eip1967.proxy.implementation") - 1
+pub const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// keccak256("eip1967.proxy.admin") - 1
+pub const EIP1967_ADMIN_SLOT: &str =
+    "0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103";
+/// keccak256("eip1967.proxy.beacon") - 1
+pub const EIP1967_BEACON_SLOT: &str =
+    "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// keccak256("org.zeppelinos.proxy.implementation"), legacy OpenZeppelin proxies
+pub const ZEPPELINOS_IMPLEMENTATION_SLOT: &str =
+    "0x7050c9e0f4ca769c69bd3a8ef740bc37934f8e2c036e5a723fd8ee048ed3f8c3";
+/// keccak256("org.zeppelinos.proxy.admin")
+pub const ZEPPELINOS_ADMIN_SLOT: &str =
+    "0x10d6a54a4754c8869d6886b5f5d7fbfa5b4522237ea5c60d11bc4e7a1ff9390b";
+/// keccak256("PROXIABLE"), EIP-1822 UUPS
+pub const EIP1822_PROXIABLE_SLOT: &str =
+    "0xc5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7";
+
+// implementation()
+const BEACON_IMPLEMENTATION_SELECTOR: &str = "0x5c60da1b";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    Eip1967,
+    Eip1967Beacon,
+    Eip1822,
+    ZeppelinOs,
+    /// No standard slot is set
+    NotAProxy,
+}
+
+impl fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProxyKind::Eip1967 => "EIP-1967 transparent/UUPS",
+            ProxyKind::Eip1967Beacon => "EIP-1967 beacon",
+            ProxyKind::Eip1822 => "EIP-1822 UUPS",
+            ProxyKind::ZeppelinOs => "OpenZeppelin legacy (zeppelinos)",
+            ProxyKind::NotAProxy => "not a proxy",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyInfo {
+    pub kind: ProxyKind,
+    pub implementation: Option<[u8; 20]>,
+    pub admin: Option<[u8; 20]>,
+    pub beacon: Option<[u8; 20]>,
+}
+
+/// The address stored in the low 20 bytes of a word, `None` when zero
+pub fn address_from_word(word: &[u8; 32]) -> Option<[u8; 20]> {
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(&word[12..]);
+    (addr != [0u8; 20]).then_some(addr)
+}
+
+/// Read the standard proxy slots and resolve the implementation, following beacons
+pub async fn detect_proxy(rpc_url: &str, address: &str, block: BlockId) -> Result<ProxyInfo> {
+    let read = |slot: &'static str| async move {
+        let raw = get_storage_at(rpc_url, address, slot, block).await?;
+        Ok::<_, anyhow::Error>(address_from_word(&parse_word(&raw)?))
+    };
+
+    let admin = match read(EIP1967_ADMIN_SLOT).await? {
+        Some(admin) => Some(admin),
+        None => read(ZEPPELINOS_ADMIN_SLOT).await?,
+    };
+    let mut info = ProxyInfo {
+        kind: ProxyKind::NotAProxy,
+        implementation: None,
+        admin,
+        beacon: None,
+    };
+
+    if let Some(implementation) = read(EIP1967_IMPLEMENTATION_SLOT).await? {
+        info.kind = ProxyKind::Eip1967;
+        info.implementation = Some(implementation);
+    } else if let Some(beacon) = read(EIP1967_BEACON_SLOT).await? {
+        let beacon_hex = format!("0x{}", hex::encode(beacon));
+        let ret = eth_call(rpc_url, &beacon_hex, BEACON_IMPLEMENTATION_SELECTOR, block).await?;
+        info.kind = ProxyKind::Eip1967Beacon;
+        info.beacon = Some(beacon);
+        info.implementation = address_from_word(&parse_word(&ret)?);
+    } else if let Some(implementation) = read(EIP1822_PROXIABLE_SLOT).await? {
+        info.kind = ProxyKind::Eip1822;
+        info.implementation = Some(implementation);
+    } else if let Some(implementation) = read(ZEPPELINOS_IMPLEMENTATION_SLOT).await? {
+        info.kind = ProxyKind::ZeppelinOs;
+        info.implementation = Some(implementation);
+    }
+    Ok(info)
+}
+
+/// keccak256(id) - 1, the EIP-1967 way of picking a slot with no known preimage
+pub fn eip1967_slot(id: &str) -> Slot {
+    Slot(keccak256(id.as_bytes())).wrapping_sub(&Slot::from(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_slots_match_their_ids() {
+        let hashed = |id: &str| Slot(keccak256(id.as_bytes())).to_string();
+
+        assert_eq!(
+            eip1967_slot("eip1967.proxy.implementation").to_string(),
+            EIP1967_IMPLEMENTATION_SLOT
+        );
+        assert_eq!(
+            eip1967_slot("eip1967.proxy.admin").to_string(),
+            EIP1967_ADMIN_SLOT
+        );
+        assert_eq!(
+            eip1967_slot("eip1967.proxy.beacon").to_string(),
+            EIP1967_BEACON_SLOT
+        );
+        assert_eq!(
+            hashed("org.zeppelinos.proxy.implementation"),
+            ZEPPELINOS_IMPLEMENTATION_SLOT
+        );
+        assert_eq!(hashed("org.zeppelinos.proxy.admin"), ZEPPELINOS_ADMIN_SLOT);
+        assert_eq!(hashed("PROXIABLE"), EIP1822_PROXIABLE_SLOT);
+    }
+
+    #[test]
+    fn test_address_from_word() -> Result<()> {
+        let word =
+            parse_word("0x000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")?;
+        assert_eq!(
+            address_from_word(&word).map(hex::encode).as_deref(),
+            Some("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+        );
+        assert_eq!(address_from_word(&[0u8; 32]), None);
+        Ok(())
+    }
+}
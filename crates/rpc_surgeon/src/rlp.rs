@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+
+/// A decoded RLP item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rlp {
+    Bytes(Vec<u8>),
+    List(Vec<Rlp>),
+}
+
+impl Rlp {
+    pub fn as_bytes(&self) -> Result<&[u8]> {
+        match self {
+            Rlp::Bytes(bytes) => Ok(bytes),
+            Rlp::List(_) => anyhow::bail!("Expected RLP bytes, found a list"),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[Rlp]> {
+        match self {
+            Rlp::List(items) => Ok(items),
+            Rlp::Bytes(_) => anyhow::bail!("Expected RLP list, found bytes"),
+        }
+    }
+}
+
+/// Decode exactly one RLP item spanning the whole input
+pub fn decode(data: &[u8]) -> Result<Rlp> {
+    let (item, consumed) = decode_item(data)?;
+    anyhow::ensure!(
+        consumed == data.len(),
+        "Trailing bytes after RLP item ({} of {})",
+        consumed,
+        data.len()
+    );
+    Ok(item)
+}
+
+// Returns the item and how many bytes it used
+fn decode_item(data: &[u8]) -> Result<(Rlp, usize)> {
+    let prefix = *data.first().context("Empty RLP input")?;
+
+    match prefix {
+        // Single byte
+        0x00..=0x7f => Ok((Rlp::Bytes(vec![prefix]), 1)),
+        // Short string
+        0x80..=0xb7 => {
+            let len = usize::from(prefix - 0x80);
+            let payload = slice(data, 1, len)?;
+            anyhow::ensure!(
+                !(len == 1 && payload[0] < 0x80),
+                "Non-canonical single byte encoding"
+            );
+            Ok((Rlp::Bytes(payload.to_vec()), 1 + len))
+        }
+        // Long string
+        0xb8..=0xbf => {
+            let len_of_len = usize::from(prefix - 0xb7);
+            let len = read_length(slice(data, 1, len_of_len)?)?;
+            let payload = slice(data, 1 + len_of_len, len)?;
+            Ok((Rlp::Bytes(payload.to_vec()), 1 + len_of_len + len))
+        }
+        // Short list
+        0xc0..=0xf7 => {
+            let len = usize::from(prefix - 0xc0);
+            let items = decode_list(slice(data, 1, len)?)?;
+            Ok((Rlp::List(items), 1 + len))
+        }
+        // Long list
+        0xf8..=0xff => {
+            let len_of_len = usize::from(prefix - 0xf7);
+            let len = read_length(slice(data, 1, len_of_len)?)?;
+            let items = decode_list(slice(data, 1 + len_of_len, len)?)?;
+            Ok((Rlp::List(items), 1 + len_of_len + len))
+        }
+    }
+}
+
+fn decode_list(mut payload: &[u8]) -> Result<Vec<Rlp>> {
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (item, consumed) = decode_item(payload)?;
+        items.push(item);
+        payload = &payload[consumed..];
+    }
+    Ok(items)
+}
+
+fn slice(data: &[u8], start: usize, len: usize) -> Result<&[u8]> {
+    start
+        .checked_add(len)
+        .and_then(|end| data.get(start..end))
+        .context("RLP item shorter than its prefix")
+}
+
+fn read_length(bytes: &[u8]) -> Result<usize> {
+    anyhow::ensure!(
+        bytes.first().is_some_and(|b| *b != 0),
+        "Non-canonical RLP length"
+    );
+    anyhow::ensure!(bytes.len() <= 8, "RLP length too large");
+    let len = bytes.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+    usize::try_from(len).context("RLP length too large")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Minimal encoder to build fixtures
+    pub(crate) fn encode(item: &Rlp) -> Vec<u8> {
+        let (payload, short, long) = match item {
+            Rlp::Bytes(bytes) if bytes.len() == 1 && bytes[0] < 0x80 => return bytes.clone(),
+            Rlp::Bytes(bytes) => (bytes.clone(), 0x80, 0xb7),
+            Rlp::List(items) => (items.iter().flat_map(encode).collect(), 0xc0, 0xf7),
+        };
+
+        let mut out = Vec::new();
+        if payload.len() <= 55 {
+            out.push(short + payload.len() as u8);
+        } else {
+            let len_bytes: Vec<u8> = payload
+                .len()
+                .to_be_bytes()
+                .into_iter()
+                .skip_while(|b| *b == 0)
+                .collect();
+            out.push(long + len_bytes.len() as u8);
+            out.extend(len_bytes);
+        }
+        out.extend(payload);
+        out
+    }
+
+    #[test]
+    fn test_decode_nested_list() -> Result<()> {
+        // ["cat", ["dog"], ""]
+        let data = hex::decode("ca83636174c483646f6780")?;
+        let item = decode(&data)?;
+        let items = item.as_list()?;
+        assert_eq!(items[0].as_bytes()?, b"cat");
+        assert_eq!(items[1].as_list()?[0].as_bytes()?, b"dog");
+        assert!(items[2].as_bytes()?.is_empty());
+        assert_eq!(encode(&item), data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_truncated_and_trailing() {
+        assert!(decode(&[0x83, 0x61, 0x62]).is_err());
+        assert!(decode(&[0x80, 0x00]).is_err());
+        assert!(decode(&[0x81, 0x05]).is_err());
+    }
+}
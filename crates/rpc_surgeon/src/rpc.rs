@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Serialize)]
+pub(crate) struct JsonRpcRequest {
+    jsonrpc: String,
+    method: String,
+    params: serde_json::Value,
+    id: u64,
+}
+
+impl JsonRpcRequest {
+    pub(crate) fn new(method: &str, params: serde_json::Value, id: u64) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id,
+        }
+    }
+}
+
+// `result` defaults to null so methods returning null (unknown block, ...) still decode
+#[derive(Deserialize, Debug)]
+pub(crate) struct JsonRpcResponse {
+    #[serde(default)]
+    pub(crate) result: serde_json::Value,
+    pub(crate) error: Option<ErrorObject>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ErrorObject {
+    code: i64,
+    message: String,
+    data: Option<serde_json::Value>,
+}
+
+impl From<ErrorObject> for RpcError {
+    fn from(error: ErrorObject) -> Self {
+        RpcError::JsonRpc {
+            code: error.code,
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
+
+/// Why a JSON-RPC call failed.
+/// Library functions return it inside `anyhow::Error`, recover it with `downcast_ref::<RpcError>()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    /// Connection, TLS or timeout failure before a response arrived
+    Transport(String),
+    /// Non-2xx status without a JSON-RPC error body
+    Http { status: u16, body: String },
+    /// Error object returned by the node
+    JsonRpc {
+        code: i64,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
+    /// Response that is not JSON-RPC, or a `result` of the wrong shape
+    Decode(String),
+}
+
+impl RpcError {
+    /// Worth retrying as is: network failures, rate limits, overloaded nodes
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RpcError::Transport(_) => true,
+            RpcError::Http { status, .. } => *status == 429 || *status >= 500,
+            // -32005: limit exceeded (EIP-1474), 429: rate limited (Infura, Alchemy)
+            RpcError::JsonRpc { code, message, .. } => {
+                *code == -32005 || *code == 429 || message.to_lowercase().contains("rate limit")
+            }
+            RpcError::Decode(_) => false,
+        }
+    }
+
+    /// The node pruned the requested state, an archive node is needed
+    pub fn is_missing_state(&self) -> bool {
+        let RpcError::JsonRpc { message, .. } = self else {
+            return false;
+        };
+        let message = message.to_lowercase();
+        [
+            "missing trie node",
+            "header not found",
+            "state is not available",
+            "historical state",
+        ]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+    }
+
+    pub(crate) fn transport(error: &reqwest::Error) -> Self {
+        // reqwest hides the root cause (connection refused, dns, ...) in the source chain
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            // hyper already folds some causes into its own message
+            let cause_message = cause.to_string();
+            if !message.contains(&cause_message) {
+                message.push_str(&format!(": {}", cause_message));
+            }
+            source = cause.source();
+        }
+        RpcError::Transport(message)
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Transport(message) => write!(f, "RPC transport error: {}", message),
+            RpcError::Http { status, body } => write!(f, "RPC HTTP {}: {}", status, body),
+            RpcError::JsonRpc { code, message, .. } => {
+                write!(f, "RPC error {}: {}", code, message)
+            }
+            RpcError::Decode(message) => write!(f, "Invalid RPC response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Block to read state at: a tag, a number or a hash (EIP-1898)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockId {
+    #[default]
+    Latest,
+    Earliest,
+    Safe,
+    Finalized,
+    Pending,
+    Number(u64),
+    Hash([u8; 32]),
+}
+
+impl BlockId {
+    /// JSON-RPC block parameter
+    pub fn to_param(&self) -> serde_json::Value {
+        match self {
+            BlockId::Number(n) => serde_json::json!(format!("0x{:x}", n)),
+            BlockId::Hash(hash) => {
+                serde_json::json!({ "blockHash": format!("0x{}", hex::encode(hash)) })
+            }
+            tag => serde_json::json!(tag.to_string()),
+        }
+    }
+}
+
+/// `latest`, `safe`, ..., decimal or `0x` block number, or a 32-byte `0x` block hash
+impl FromStr for BlockId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "latest" => return Ok(BlockId::Latest),
+            "earliest" => return Ok(BlockId::Earliest),
+            "safe" => return Ok(BlockId::Safe),
+            "finalized" => return Ok(BlockId::Finalized),
+            "pending" => return Ok(BlockId::Pending),
+            _ => {}
+        }
+
+        match s.strip_prefix("0x") {
+            Some(digits) if digits.len() == 64 => {
+                let mut hash = [0u8; 32];
+                hex::decode_to_slice(digits, &mut hash)
+                    .with_context(|| format!("Invalid block hash: {}", s))?;
+                Ok(BlockId::Hash(hash))
+            }
+            Some(digits) => u64::from_str_radix(digits, 16)
+                .map(BlockId::Number)
+                .with_context(|| format!("Invalid block number: {}", s)),
+            None => s
+                .parse()
+                .map(BlockId::Number)
+                .with_context(|| format!("Invalid block: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockId::Latest => write!(f, "latest"),
+            BlockId::Earliest => write!(f, "earliest"),
+            BlockId::Safe => write!(f, "safe"),
+            BlockId::Finalized => write!(f, "finalized"),
+            BlockId::Pending => write!(f, "pending"),
+            BlockId::Number(n) => write!(f, "{}", n),
+            BlockId::Hash(hash) => write!(f, "0x{}", hex::encode(hash)),
+        }
+    }
+}
+
+pub async fn get_storage_at(
+    rpc_url: &str,
+    address: &str,
+    slot: &str,
+    block: BlockId,
+) -> Result<String> {
+    Ok(call(
+        rpc_url,
+        "eth_getStorageAt",
+        serde_json::json!([address, slot, block.to_param()]),
+    )
+    .await?)
+}
+
+/// Latest block number seen by the node
+pub async fn block_number(rpc_url: &str) -> Result<u64> {
+    let raw: String = call(rpc_url, "eth_blockNumber", serde_json::json!([])).await?;
+    u64::from_str_radix(raw.trim_start_matches("0x"), 16)
+        .with_context(|| format!("Invalid block number: {}", raw))
+}
+
+/// Pin a tag (`latest`, `safe`, ...) to the block number it currently means, numbers
+/// and hashes are returned as is. Reads spread over several requests should all use it.
+pub async fn resolve_block(rpc_url: &str, block: BlockId) -> Result<BlockId> {
+    #[derive(Deserialize)]
+    struct Header {
+        number: String,
+    }
+
+    match block {
+        BlockId::Number(_) | BlockId::Hash(_) => return Ok(block),
+        BlockId::Latest => return Ok(BlockId::Number(block_number(rpc_url).await?)),
+        _ => {}
+    }
+    let header: Option<Header> = call(
+        rpc_url,
+        "eth_getBlockByNumber",
+        serde_json::json!([block.to_param(), false]),
+    )
+    .await?;
+    let number = header
+        .with_context(|| format!("Block {} not found", block))?
+        .number;
+    u64::from_str_radix(number.trim_start_matches("0x"), 16)
+        .map(BlockId::Number)
+        .with_context(|| format!("Invalid block number: {}", number))
+}
+
+/// `eth_call` with raw calldata, returns the raw hex result
+pub async fn eth_call(rpc_url: &str, to: &str, data: &str, block: BlockId) -> Result<String> {
+    Ok(call(
+        rpc_url,
+        "eth_call",
+        serde_json::json!([{ "to": to, "data": data }, block.to_param()]),
+    )
+    .await?)
+}
+
+/// Single JSON-RPC request, deserializing `result` into `T`
+pub(crate) async fn call<T: DeserializeOwned>(
+    rpc_url: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<T, RpcError> {
+    let client = reqwest::Client::new();
+
+    let payload = JsonRpcRequest::new(method, params, 1);
+
+    let response = client
+        .post(rpc_url)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| RpcError::transport(&e))?;
+
+    let status = response.status();
+    let body = response.text().await.map_err(|e| RpcError::transport(&e))?;
+    let parsed = parse_response(status.as_u16(), &body)?;
+
+    serde_json::from_value(parsed.result)
+        .map_err(|e| RpcError::Decode(format!("{} result: {}", method, e)))
+}
+
+// Error objects win over the HTTP status: rate limits often come as 429 with a JSON-RPC body
+pub(crate) fn parse_response(status: u16, body: &str) -> Result<JsonRpcResponse, RpcError> {
+    match serde_json::from_str::<JsonRpcResponse>(body) {
+        Ok(JsonRpcResponse {
+            error: Some(error), ..
+        }) => Err(error.into()),
+        _ if !(200..300).contains(&status) => Err(RpcError::Http {
+            status,
+            body: body.chars().take(200).collect(),
+        }),
+        Ok(parsed) => Ok(parsed),
+        Err(e) => Err(RpcError::Decode(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Local JSON-RPC node answering each `(method, params)` with `respond`'s result
+    pub(crate) async fn mock_node<F>(respond: F) -> Result<String>
+    where
+        F: Fn(&str, &serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    {
+        mock_server(move |body| {
+            let reply = |call: &serde_json::Value| {
+                let method = call["method"].as_str().unwrap_or_default();
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": call["id"],
+                    "result": respond(method, &call["params"]),
+                })
+            };
+            match body.as_array() {
+                Some(calls) => serde_json::Value::Array(calls.iter().map(reply).collect()),
+                None => reply(body),
+            }
+        })
+        .await
+    }
+
+    /// Local HTTP server answering each request body with `handle`'s raw JSON body
+    pub(crate) async fn mock_server<F>(handle: F) -> Result<String>
+    where
+        F: Fn(&serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let handle = Arc::new(handle);
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handle = Arc::clone(&handle);
+                tokio::spawn(async move {
+                    let _ = answer(stream, handle.as_ref()).await;
+                });
+            }
+        });
+        Ok(url)
+    }
+
+    async fn answer<F>(mut stream: TcpStream, handle: &F) -> Result<()>
+    where
+        F: Fn(&serde_json::Value) -> serde_json::Value,
+    {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        let body_start = loop {
+            let n = stream.read(&mut buf).await?;
+            anyhow::ensure!(n > 0, "Connection closed mid-request");
+            request.extend_from_slice(&buf[..n]);
+            if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+
+        let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+        let length: usize = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .context("Missing content-length")?
+            .trim()
+            .parse()?;
+        while request.len() < body_start + length {
+            let n = stream.read(&mut buf).await?;
+            anyhow::ensure!(n > 0, "Connection closed mid-body");
+            request.extend_from_slice(&buf[..n]);
+        }
+
+        let body: serde_json::Value = serde_json::from_slice(&request[body_start..])?;
+        let out = handle(&body).to_string();
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            out.len(),
+            out
+        );
+        stream.write_all(response.as_bytes()).await?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_id_params() -> Result<()> {
+        assert_eq!("finalized".parse::<BlockId>()?, BlockId::Finalized);
+        assert_eq!("19000000".parse::<BlockId>()?.to_param(), "0x121eac0");
+        assert_eq!("0x121eac0".parse::<BlockId>()?, BlockId::Number(19_000_000));
+
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(
+            hash.parse::<BlockId>()?.to_param(),
+            serde_json::json!({ "blockHash": hash })
+        );
+        assert!("soon".parse::<BlockId>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rpc_error_classification() {
+        let missing = parse_response(
+            200,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"missing trie node 1a2b (path )"}}"#,
+        );
+        assert!(missing.as_ref().is_err_and(RpcError::is_missing_state));
+        assert!(missing.is_err_and(|e| !e.is_retryable()));
+
+        let limited = parse_response(429, "Too Many Requests");
+        assert!(matches!(limited, Err(RpcError::Http { status: 429, .. })));
+        assert!(limited.is_err_and(|e| e.is_retryable()));
+
+        assert!(matches!(
+            parse_response(200, "<html>"),
+            Err(RpcError::Decode(_))
+        ));
+        assert!(
+            parse_response(200, r#"{"jsonrpc":"2.0","id":1,"result":null}"#)
+                .is_ok_and(|r| r.result.is_null())
+        );
+    }
+}
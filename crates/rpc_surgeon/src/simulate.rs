@@ -0,0 +1,82 @@
+use crate::rpc::{BlockId, call};
+use crate::slot::Slot;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Hypothetical storage for `eth_call`, applied as `stateDiff` so untouched slots keep their value
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateOverride {
+    storage: BTreeMap<String, BTreeMap<Slot, [u8; 32]>>,
+}
+
+impl StateOverride {
+    pub fn set_storage(&mut self, address: &str, slot: Slot, value: [u8; 32]) -> &mut Self {
+        self.storage
+            .entry(address.to_lowercase())
+            .or_default()
+            .insert(slot, value);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty()
+    }
+
+    /// The `stateOverride` object: `{ address: { stateDiff: { slot: value } } }`
+    pub fn to_json(&self) -> serde_json::Value {
+        let accounts: serde_json::Map<String, serde_json::Value> = self
+            .storage
+            .iter()
+            .map(|(address, slots)| {
+                let diff: serde_json::Map<String, serde_json::Value> = slots
+                    .iter()
+                    .map(|(slot, value)| {
+                        (
+                            slot.to_string(),
+                            serde_json::json!(format!("0x{}", hex::encode(value))),
+                        )
+                    })
+                    .collect();
+                (address.clone(), serde_json::json!({ "stateDiff": diff }))
+            })
+            .collect();
+        serde_json::Value::Object(accounts)
+    }
+}
+
+/// `eth_call` with raw calldata on top of `overrides`, returns the raw hex result
+pub async fn eth_call_with_overrides(
+    rpc_url: &str,
+    to: &str,
+    data: &str,
+    block: BlockId,
+    overrides: &StateOverride,
+) -> Result<String> {
+    Ok(call(
+        rpc_url,
+        "eth_call",
+        serde_json::json!([{ "to": to, "data": data }, block.to_param(), overrides.to_json()]),
+    )
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_override_shape() {
+        let mut overrides = StateOverride::default();
+        overrides
+            .set_storage("0xA0b8", Slot::from(9), Slot::from(1).0)
+            .set_storage("0xa0b8", Slot::from(10), Slot::from(2).0);
+
+        let json = overrides.to_json();
+        let diff = &json["0xa0b8"]["stateDiff"];
+        assert_eq!(diff.as_object().map(|d| d.len()), Some(2));
+        assert_eq!(
+            diff[Slot::from(9).to_string()],
+            format!("0x{}", hex::encode(Slot::from(1).0))
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::key::{MappingKey, parse_uint};
+use anyhow::Result;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Compiler whose storage conventions apply when deriving slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compiler {
+    /// Mapping slots are keccak256(h(k) . p)
+    #[default]
+    Solidity,
+    /// Mapping slots are keccak256(p . h(k))
+    Vyper,
+}
+
+impl fmt::Display for Compiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compiler::Solidity => write!(f, "solidity"),
+            Compiler::Vyper => write!(f, "vyper"),
+        }
+    }
+}
+
+impl FromStr for Compiler {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "solidity" | "solc" => Ok(Compiler::Solidity),
+            "vyper" => Ok(Compiler::Vyper),
+            _ => anyhow::bail!("Unknown compiler: {} (solidity, vyper)", s),
+        }
+    }
+}
+
+/// A 256-bit storage slot, big-endian.
+///
+/// Arithmetic wraps modulo 2^256, like the EVM does for storage offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Slot(pub [u8; 32]);
+
+impl Slot {
+    pub const ZERO: Slot = Slot([0u8; 32]);
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// The slot as a small integer, if it fits in 64 bits
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.0[..24].iter().any(|b| *b != 0) {
+            return None;
+        }
+        let mut raw = [0u8; 8];
+        raw.copy_from_slice(&self.0[24..]);
+        Some(u64::from_be_bytes(raw))
+    }
+
+    /// keccak256 of the raw slot bytes, where dynamic data for this slot begins
+    pub fn hashed(&self) -> Slot {
+        Slot(keccak256(&self.0))
+    }
+
+    /// Slot of `mapping[key]` when this slot is the mapping position
+    // keccak256(h(k) + p)
+    pub fn mapping(&self, key: &MappingKey) -> Slot {
+        self.mapping_for(key, Compiler::Solidity)
+    }
+
+    /// Slot of `mapping[key]`, hashing key and position in the compiler's order
+    pub fn mapping_for(&self, key: &MappingKey, compiler: Compiler) -> Slot {
+        let mut hasher = Keccak256::new();
+        match compiler {
+            Compiler::Solidity => {
+                hasher.update(key.encode());
+                hasher.update(self.0);
+            }
+            Compiler::Vyper => {
+                hasher.update(self.0);
+                hasher.update(key.encode());
+            }
+        }
+        Slot(hasher.finalize().into())
+    }
+
+    pub fn wrapping_add(&self, other: &Slot) -> Slot {
+        let mut out = [0u8; 32];
+        let mut carry = 0u16;
+        for i in (0..32).rev() {
+            let sum = u16::from(self.0[i]) + u16::from(other.0[i]) + carry;
+            out[i] = sum as u8;
+            carry = sum >> 8;
+        }
+        Slot(out)
+    }
+
+    pub fn wrapping_sub(&self, other: &Slot) -> Slot {
+        let mut out = [0u8; 32];
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let mut diff = i16::from(self.0[i]) - i16::from(other.0[i]) - borrow;
+            borrow = i16::from(diff < 0);
+            if diff < 0 {
+                diff += 256;
+            }
+            out[i] = diff as u8;
+        }
+        Slot(out)
+    }
+
+    /// `self + n`, e.g. the n-th word of a struct or a static array
+    pub fn offset(&self, n: u64) -> Slot {
+        self.wrapping_add(&Slot::from(n))
+    }
+
+    /// Location of `array[index]` when this slot holds a dynamic array (`T[]`) length.
+    /// Elements live at keccak256(p) + index * size, see `element_location`.
+    pub fn array_element(&self, index: u64, element_size: u64) -> Result<(Slot, usize)> {
+        element_location(self.hashed(), index, element_size)
+    }
+
+    /// Location of `array[index]` for a dynamic array under the compiler's layout.
+    /// Vyper `DynArray` keeps the length at p and unpacked elements from p + 1.
+    pub fn array_element_for(
+        &self,
+        index: u64,
+        element_size: u64,
+        compiler: Compiler,
+    ) -> Result<(Slot, usize)> {
+        match compiler {
+            Compiler::Solidity => self.array_element(index, element_size),
+            Compiler::Vyper => element_location(self.offset(1), index, unpacked(element_size)),
+        }
+    }
+
+    /// Location of `array[index]` for a fixed-size array (`T[N]`) starting at this slot
+    pub fn static_array_element(&self, index: u64, element_size: u64) -> Result<(Slot, usize)> {
+        element_location(*self, index, element_size)
+    }
+}
+
+impl From<u64> for Slot {
+    fn from(value: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        Slot(bytes)
+    }
+}
+
+impl From<[u8; 32]> for Slot {
+    fn from(bytes: [u8; 32]) -> Self {
+        Slot(bytes)
+    }
+}
+
+/// Decimal (`3`) or `0x` hex (`0x360894a1...`) position
+impl FromStr for Slot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_uint(s, 256).map(Slot)
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+/// Slot and byte offset (from the low-order end, as solc reports it) of the
+/// `index`-th element of `element_size` bytes stored from `data_start`.
+/// Elements of 32 bytes or less are packed `32 / size` per slot (one per slot
+/// above 16 bytes), larger ones (structs) take `ceil(size / 32)` whole slots.
+pub fn element_location(data_start: Slot, index: u64, element_size: u64) -> Result<(Slot, usize)> {
+    anyhow::ensure!(element_size > 0, "Element size must be at least 1 byte");
+
+    if element_size <= 32 {
+        let per_slot = 32 / element_size;
+        let slot = data_start.offset(index / per_slot);
+        let offset = (index % per_slot) * element_size;
+        return Ok((slot, offset as usize));
+    }
+
+    // index * words can exceed u64, do it in u128
+    let words = u128::from(element_size.div_ceil(32));
+    let mut delta = [0u8; 32];
+    delta[16..].copy_from_slice(&(u128::from(index) * words).to_be_bytes());
+    Ok((data_start.wrapping_add(&Slot(delta)), 0))
+}
+
+// Vyper never packs: every element takes whole slots
+fn unpacked(element_size: u64) -> u64 {
+    element_size.div_ceil(32).max(1) * 32
+}
+
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_decimal_and_hex() -> Result<()> {
+        assert_eq!("3".parse::<Slot>()?, Slot::from(3));
+        assert_eq!("0x03".parse::<Slot>()?, Slot::from(3));
+        let max = format!("0x{}", "ff".repeat(32));
+        assert_eq!(max.parse::<Slot>()?, Slot([0xff; 32]));
+        assert!(format!("0x1{}", "00".repeat(32)).parse::<Slot>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic_wraps() {
+        let max = Slot([0xff; 32]);
+        assert_eq!(max.offset(1), Slot::ZERO);
+        assert_eq!(Slot::ZERO.wrapping_sub(&Slot::from(1)), max);
+        assert_eq!(Slot::from(0x1ff).offset(1), Slot::from(0x200));
+    }
+
+    #[test]
+    fn test_array_element_packing() -> Result<()> {
+        let data = Slot::from(2).hashed();
+
+        // address[] : one per slot
+        assert_eq!(Slot::from(2).array_element(3, 20)?, (data.offset(3), 0));
+        // uint64[] : four per slot
+        assert_eq!(Slot::from(2).array_element(5, 8)?, (data.offset(1), 8));
+        // struct { uint256; uint256; uint256 }[] : three slots each
+        assert_eq!(Slot::from(2).array_element(2, 96)?, (data.offset(6), 0));
+        // uint128[4] in place
+        assert_eq!(
+            Slot::from(2).static_array_element(3, 16)?,
+            (Slot::from(3), 16)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vyper_mapping_order() {
+        let key = MappingKey::Uint(Slot::from(7).0);
+        let mut preimage = Slot::from(2).0.to_vec();
+        preimage.extend(key.encode());
+
+        assert_eq!(
+            Slot::from(2).mapping_for(&key, Compiler::Vyper),
+            Slot(keccak256(&preimage))
+        );
+        assert_ne!(
+            Slot::from(2).mapping_for(&key, Compiler::Vyper),
+            Slot::from(2).mapping(&key)
+        );
+    }
+
+    #[test]
+    fn test_vyper_dyn_array_is_unhashed_and_unpacked() -> Result<()> {
+        // DynArray[uint64, N] at slot 4: element 5 sits at 4 + 1 + 5
+        assert_eq!(
+            Slot::from(4).array_element_for(5, 8, Compiler::Vyper)?,
+            (Slot::from(10), 0)
+        );
+        assert_eq!(
+            Slot::from(4).array_element_for(5, 8, Compiler::Solidity)?,
+            Slot::from(4).array_element(5, 8)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashed_slot_zero() {
+        // Data start of a dynamic array declared at slot 0
+        assert_eq!(
+            Slot::ZERO.hashed().to_string(),
+            "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::decode::parse_word;
+use crate::rpc::call;
+use crate::slot::Slot;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// One storage slot returned by `debug_storageRangeAt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeEntry {
+    /// Secure trie key, keccak256(slot)
+    pub hashed_key: Slot,
+    /// Slot preimage, when the node keeps preimages
+    pub key: Option<Slot>,
+    pub value: [u8; 32],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageRangeResult {
+    storage: HashMap<String, RangeItem>,
+    next_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RangeItem {
+    key: Option<String>,
+    value: String,
+}
+
+/// One page of `address` storage in trie key order from `start`, as seen before
+/// transaction `tx_index` of `block_hash`. Returns the entries and the next start key.
+pub async fn storage_range_at(
+    rpc_url: &str,
+    block_hash: [u8; 32],
+    tx_index: u64,
+    address: &str,
+    start: Slot,
+    max_results: usize,
+) -> Result<(Vec<RangeEntry>, Option<Slot>)> {
+    let result: StorageRangeResult = call(
+        rpc_url,
+        "debug_storageRangeAt",
+        serde_json::json!([
+            format!("0x{}", hex::encode(block_hash)),
+            tx_index,
+            address,
+            start.to_string(),
+            max_results
+        ]),
+    )
+    .await?;
+    parse_range(result)
+}
+
+fn parse_range(result: StorageRangeResult) -> Result<(Vec<RangeEntry>, Option<Slot>)> {
+    let mut entries = Vec::with_capacity(result.storage.len());
+    for (hashed_key, item) in result.storage {
+        entries.push(RangeEntry {
+            hashed_key: hashed_key.parse()?,
+            key: item.key.map(|key| key.parse()).transpose()?,
+            value: parse_word(&item.value)?,
+        });
+    }
+    entries.sort_by_key(|entry| entry.hashed_key);
+
+    let next_key = result.next_key.map(|key| key.parse()).transpose()?;
+    Ok((entries, next_key))
+}
+
+/// Every non-zero slot of `address` once `block` is applied, paging `debug_storageRangeAt`.
+/// Reads the start of block + 1, so that block must exist.
+pub async fn storage_dump(
+    rpc_url: &str,
+    address: &str,
+    block: u64,
+    page_size: usize,
+) -> Result<Vec<RangeEntry>> {
+    anyhow::ensure!(page_size > 0, "Page size must be at least 1");
+    let next_block = block.checked_add(1).context("Block number overflow")?;
+    let block_hash = block_hash(rpc_url, next_block).await.with_context(|| {
+        format!(
+            "State after block {} is read at block {}",
+            block, next_block
+        )
+    })?;
+
+    let mut entries = Vec::new();
+    let mut start = Slot::ZERO;
+    loop {
+        let (page, next) =
+            storage_range_at(rpc_url, block_hash, 0, address, start, page_size).await?;
+        entries.extend(page);
+        match next {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    Ok(entries)
+}
+
+/// Hash of a block by number
+pub async fn block_hash(rpc_url: &str, number: u64) -> Result<[u8; 32]> {
+    #[derive(Deserialize)]
+    struct Header {
+        hash: String,
+    }
+
+    let header: Option<Header> = call(
+        rpc_url,
+        "eth_getBlockByNumber",
+        serde_json::json!([format!("0x{:x}", number), false]),
+    )
+    .await?;
+    let header = header.with_context(|| format!("Block {} not found", number))?;
+    parse_word(&header.hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_entries_sorted_with_optional_preimage() -> Result<()> {
+        // geth's shape, one key without a preimage
+        let json = serde_json::json!({
+            "storage": {
+                "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563": {
+                    "key": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "value": "0x000000000000000000000000000000000000000000000000000000000000002a"
+                },
+                "0x0000000000000000000000000000000000000000000000000000000000000001": {
+                    "key": null,
+                    "value": "0x01"
+                }
+            },
+            "nextKey": "0x3000000000000000000000000000000000000000000000000000000000000000"
+        });
+        let (entries, next) = parse_range(serde_json::from_value(json)?)?;
+
+        assert_eq!(entries[0].hashed_key, Slot::from(1));
+        assert_eq!(entries[0].key, None);
+        assert_eq!(entries[1].hashed_key, Slot::ZERO.hashed());
+        assert_eq!(entries[1].key, Some(Slot::ZERO));
+        assert_eq!(entries[1].value[31], 0x2a);
+        assert_eq!(next.map(|n| n.as_bytes()[0]), Some(0x30));
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use crate::decode::parse_word;
+use crate::diff::SlotDiff;
+use crate::key::parse_address;
+use crate::rpc::call;
+use crate::slot::Slot;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+// Slot -> (value before, value after)
+type SlotValues = BTreeMap<Slot, ([u8; 32], [u8; 32])>;
+
+/// Storage slots one account had changed by a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChanges {
+    pub address: String,
+    pub slots: Vec<SlotDiff>,
+}
+
+#[derive(Deserialize)]
+struct PrestateDiff {
+    #[serde(default)]
+    pre: HashMap<String, AccountState>,
+    #[serde(default)]
+    post: HashMap<String, AccountState>,
+}
+
+#[derive(Deserialize)]
+struct AccountState {
+    #[serde(default)]
+    storage: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct Transaction {
+    from: String,
+    to: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Receipt {
+    contract_address: Option<String>,
+    logs: Vec<Log>,
+}
+
+#[derive(Deserialize)]
+struct Log {
+    address: String,
+    topics: Vec<String>,
+}
+
+/// Every storage slot a transaction changed, per contract, from the prestateTracer in diff mode
+pub async fn trace_storage_changes(rpc_url: &str, tx_hash: &str) -> Result<Vec<AccountChanges>> {
+    let trace: serde_json::Value = call(
+        rpc_url,
+        "debug_traceTransaction",
+        serde_json::json!([
+            tx_hash,
+            { "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } }
+        ]),
+    )
+    .await?;
+    storage_changes(trace)
+}
+
+// Diff mode lists only modified slots: `pre` holds old values, `post` new ones,
+// and a slot cleared to zero is missing from `post`
+fn storage_changes(trace: serde_json::Value) -> Result<Vec<AccountChanges>> {
+    let diff: PrestateDiff = serde_json::from_value(trace).context("Invalid prestate diff")?;
+
+    let mut accounts: BTreeMap<String, SlotValues> = BTreeMap::new();
+    for (states, is_post) in [(&diff.pre, false), (&diff.post, true)] {
+        for (address, state) in states {
+            let slots = accounts.entry(address.to_lowercase()).or_default();
+            for (slot, value) in &state.storage {
+                let entry = slots.entry(slot.parse()?).or_default();
+                let word = parse_word(value)?;
+                if is_post {
+                    entry.1 = word;
+                } else {
+                    entry.0 = word;
+                }
+            }
+        }
+    }
+
+    Ok(accounts
+        .into_iter()
+        .map(|(address, slots)| AccountChanges {
+            address,
+            slots: slots
+                .into_iter()
+                .filter(|(_, (before, after))| before != after)
+                .map(|(slot, (before, after))| SlotDiff {
+                    slot: Some(slot),
+                    hashed_key: slot.hashed(),
+                    before,
+                    after,
+                })
+                .collect(),
+        })
+        .filter(|account| !account.slots.is_empty())
+        .collect())
+}
+
+/// Addresses a transaction involves: sender, recipient or created contract, log emitters
+/// and address-shaped log topics (ERC-20 `Transfer` from / to, ...), in order of appearance
+pub async fn transaction_addresses(rpc_url: &str, tx_hash: &str) -> Result<Vec<[u8; 20]>> {
+    let tx: Option<Transaction> = call(
+        rpc_url,
+        "eth_getTransactionByHash",
+        serde_json::json!([tx_hash]),
+    )
+    .await?;
+    let tx = tx.with_context(|| format!("Transaction {} not found", tx_hash))?;
+    let receipt: Option<Receipt> = call(
+        rpc_url,
+        "eth_getTransactionReceipt",
+        serde_json::json!([tx_hash]),
+    )
+    .await?;
+    let receipt = receipt.with_context(|| format!("Transaction {} is pending", tx_hash))?;
+
+    let mut addresses = vec![parse_address(&tx.from)?];
+    for address in tx.to.iter().chain(&receipt.contract_address) {
+        addresses.push(parse_address(address)?);
+    }
+    for log in &receipt.logs {
+        addresses.push(parse_address(&log.address)?);
+        for topic in &log.topics {
+            let word = parse_word(topic)?;
+            if word[..12] == [0u8; 12] && word[12..] != [0u8; 20] {
+                let mut address = [0u8; 20];
+                address.copy_from_slice(&word[12..]);
+                addresses.push(address);
+            }
+        }
+    }
+
+    let mut seen = Vec::new();
+    addresses.retain(|address| {
+        let new = !seen.contains(address);
+        seen.push(*address);
+        new
+    });
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_changes_from_diff_mode() -> Result<()> {
+        let word = |n: u64| format!("0x{}", hex::encode(Slot::from(n).0));
+        let balance = "0x9cca97fb08ee88532e0983a3a051466c5df908292b6899f3cdc163eb9c0b22ba";
+        let balance_slot: Slot = balance.parse()?;
+        let trace = serde_json::json!({
+            "pre": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "balance": "0x1",
+                    "storage": { balance: word(500), word(7): word(1) }
+                },
+                "0xF977814e90dA44bFA03b6295A0616a897441aceC": { "balance": "0x2" }
+            },
+            "post": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "storage": { balance: word(300), word(8): word(9) }
+                },
+                "0xF977814e90dA44bFA03b6295A0616a897441aceC": { "balance": "0x3" }
+            }
+        });
+
+        let changes = storage_changes(trace)?;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].address,
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+        );
+
+        let slots = &changes[0].slots;
+        assert_eq!(slots.len(), 3);
+        let moved = slots
+            .iter()
+            .find(|diff| diff.slot == Some(balance_slot))
+            .context("balance slot missing")?;
+        assert_eq!(
+            (moved.before, moved.after),
+            (Slot::from(500).0, Slot::from(300).0)
+        );
+        // Cleared slot is absent from post, new slot absent from pre
+        assert!(slots.contains(&SlotDiff {
+            slot: Some(Slot::from(7)),
+            hashed_key: Slot::from(7).hashed(),
+            before: Slot::from(1).0,
+            after: [0u8; 32],
+        }));
+        assert!(
+            slots
+                .iter()
+                .any(|diff| diff.slot == Some(Slot::from(8)) && diff.before == [0u8; 32])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::decode::{DecodedValue, extract_field};
+use crate::key::{parse_int, parse_uint};
+use crate::slot::Slot;
+use anyhow::Result;
+use std::cmp::Ordering;
+
+/// Alert condition on a watched value, compared as a 256-bit integer of the field's signedness
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    Above(Slot),
+    Below(Slot),
+    /// Every change is an alert (admin variables, owners, ...)
+    AnyChange,
+}
+
+impl Threshold {
+    /// True when `old -> new` moves into the alert zone; staying inside it does not re-alert.
+    /// Signed values must be sign-extended, as `field_value` does.
+    pub fn crossed(&self, old: &Slot, new: &Slot, signed: bool) -> bool {
+        let cmp = |a: &Slot, b: &Slot| compare(a, b, signed);
+        match self {
+            Threshold::Above(limit) => cmp(old, limit).is_le() && cmp(new, limit).is_gt(),
+            Threshold::Below(limit) => cmp(old, limit).is_ge() && cmp(new, limit).is_lt(),
+            Threshold::AnyChange => old != new,
+        }
+    }
+
+    /// `rose above 100`, `fell below -5`, `changed`
+    pub fn describe(&self, signed: bool) -> String {
+        match self {
+            Threshold::Above(limit) => format!("rose above {}", number(limit, signed)),
+            Threshold::Below(limit) => format!("fell below {}", number(limit, signed)),
+            Threshold::AnyChange => "changed".to_string(),
+        }
+    }
+}
+
+/// Threshold value, decimal or `0x` hex, negative only for signed fields
+pub fn parse_limit(value: &str, signed: bool) -> Result<Slot> {
+    let word = if signed {
+        parse_int(value, 256)?
+    } else {
+        parse_uint(value, 256)?
+    };
+    Ok(Slot(word))
+}
+
+// Flipping the sign bit maps two's complement order onto unsigned order
+fn compare(a: &Slot, b: &Slot, signed: bool) -> Ordering {
+    if !signed {
+        return a.cmp(b);
+    }
+    let (mut a, mut b) = (*a, *b);
+    a.0[0] ^= 0x80;
+    b.0[0] ^= 0x80;
+    a.cmp(&b)
+}
+
+fn number(value: &Slot, signed: bool) -> DecodedValue {
+    if signed {
+        DecodedValue::Int(value.0.to_vec())
+    } else {
+        DecodedValue::Uint(value.0.to_vec())
+    }
+}
+
+/// A packed field of a storage word as a 256-bit integer, sign-extended when `signed`
+pub fn field_value(word: &[u8; 32], offset: usize, size: usize, signed: bool) -> Result<Slot> {
+    let field = extract_field(word, offset, size)?;
+    let fill = if signed && field.first().is_some_and(|b| b & 0x80 != 0) {
+        0xff
+    } else {
+        0
+    };
+    let mut value = [fill; 32];
+    value[32 - field.len()..].copy_from_slice(field);
+    Ok(Slot(value))
+}
+
+/// Difference `new - old` in decimal, e.g. `+1500` or `-42`
+pub fn delta(old: &Slot, new: &Slot, signed: bool) -> String {
+    if compare(new, old, signed).is_ge() {
+        format!("+{}", DecodedValue::Uint(new.wrapping_sub(old).0.to_vec()))
+    } else {
+        format!("-{}", DecodedValue::Uint(old.wrapping_sub(new).0.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_crossing() {
+        let above = Threshold::Above(Slot::from(100));
+        assert!(above.crossed(&Slot::from(100), &Slot::from(101), false));
+        assert!(!above.crossed(&Slot::from(150), &Slot::from(200), false));
+        assert!(!above.crossed(&Slot::from(150), &Slot::from(50), false));
+
+        let below = Threshold::Below(Slot::from(100));
+        assert!(below.crossed(&Slot::from(100), &Slot::from(99), false));
+        assert!(!below.crossed(&Slot::from(99), &Slot::from(1), false));
+
+        assert!(Threshold::AnyChange.crossed(&Slot::from(1), &Slot::from(2), false));
+    }
+
+    #[test]
+    fn test_delta_and_packed_field() -> Result<()> {
+        assert_eq!(delta(&Slot::from(1000), &Slot::from(2500), false), "+1500");
+        assert_eq!(delta(&Slot::from(2500), &Slot::from(1000), false), "-1500");
+
+        let mut word = [0u8; 32];
+        word[17] = 0x01;
+        word[31] = 0x02;
+        // reserve1 (uint112, offset 14) = 1, reserve0 (offset 0) = 2
+        assert_eq!(field_value(&word, 14, 14, false)?, Slot::from(1));
+        assert_eq!(field_value(&word, 0, 14, false)?, Slot::from(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_field_thresholds() -> Result<()> {
+        // int24 at offset 0 going from 5 to -3
+        let mut old = [0u8; 32];
+        old[31] = 5;
+        let mut new = [0u8; 32];
+        new[29..].copy_from_slice(&[0xff, 0xff, 0xfd]);
+        let old = field_value(&old, 0, 3, true)?;
+        let new = field_value(&new, 0, 3, true)?;
+        assert_eq!(new, Slot(parse_int("-3", 256)?));
+
+        let below = Threshold::Below(parse_limit("0", true)?);
+        assert!(below.crossed(&old, &new, true));
+        assert!(!below.crossed(&old, &new, false));
+        let above = Threshold::Above(parse_limit("-5", true)?);
+        assert!(!above.crossed(&old, &new, true));
+        assert!(above.crossed(&Slot(parse_int("-6", 256)?), &new, true));
+        assert_eq!(above.describe(true), "rose above -5");
+        assert_eq!(delta(&old, &new, true), "-8");
+        assert!(parse_limit("-5", false).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use crate::decode::parse_word;
+use crate::rpc::{BlockId, call, get_storage_at};
+use crate::slot::Slot;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Local development node exposing a `*_setStorageAt` cheat method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevNode {
+    Anvil,
+    Hardhat,
+}
+
+impl DevNode {
+    fn set_storage_method(&self) -> &'static str {
+        match self {
+            DevNode::Anvil => "anvil_setStorageAt",
+            DevNode::Hardhat => "hardhat_setStorageAt",
+        }
+    }
+}
+
+impl FromStr for DevNode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "anvil" => Ok(DevNode::Anvil),
+            "hardhat" => Ok(DevNode::Hardhat),
+            _ => anyhow::bail!("Unknown dev node: {} (anvil, hardhat)", s),
+        }
+    }
+}
+
+impl fmt::Display for DevNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DevNode::Anvil => write!(f, "anvil"),
+            DevNode::Hardhat => write!(f, "hardhat"),
+        }
+    }
+}
+
+/// Identify the node from `web3_clientVersion` (`anvil/v0.2.0`, `HardhatNetwork/2.22.0/...`)
+pub async fn detect_dev_node(rpc_url: &str) -> Result<DevNode> {
+    let version: String = call(rpc_url, "web3_clientVersion", serde_json::json!([])).await?;
+    let lower = version.to_lowercase();
+    if lower.starts_with("anvil") {
+        Ok(DevNode::Anvil)
+    } else if lower.starts_with("hardhat") {
+        Ok(DevNode::Hardhat)
+    } else {
+        anyhow::bail!(
+            "{} does not support setStorageAt (anvil or hardhat required)",
+            version
+        )
+    }
+}
+
+/// Overwrite one storage word on a dev node
+pub async fn set_storage_at(
+    rpc_url: &str,
+    node: DevNode,
+    address: &str,
+    slot: Slot,
+    value: [u8; 32],
+) -> Result<()> {
+    let _: serde_json::Value = call(
+        rpc_url,
+        node.set_storage_method(),
+        serde_json::json!([
+            address,
+            quantity(&slot),
+            format!("0x{}", hex::encode(value))
+        ]),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Write `value` to `slot` and read it back, failing if the node did not apply it
+pub async fn write_storage(
+    rpc_url: &str,
+    node: DevNode,
+    address: &str,
+    slot: Slot,
+    value: [u8; 32],
+) -> Result<[u8; 32]> {
+    set_storage_at(rpc_url, node, address, slot, value).await?;
+
+    let raw = get_storage_at(rpc_url, address, &slot.to_string(), BlockId::Latest).await?;
+    let stored = parse_word(&raw).context("Unexpected read-back value")?;
+    anyhow::ensure!(
+        stored == value,
+        "Slot {} reads 0x{} after the write",
+        slot,
+        hex::encode(stored)
+    );
+    Ok(stored)
+}
+
+// Hardhat wants the position as a QUANTITY: 0x-prefixed, no leading zeros
+fn quantity(slot: &Slot) -> String {
+    let digits = hex::encode(slot.as_bytes());
+    let trimmed = digits.trim_start_matches('0');
+    format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::tests::mock_node;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_slot_quantity() {
+        assert_eq!(quantity(&Slot::ZERO), "0x0");
+        assert_eq!(quantity(&Slot::from(0x1f)), "0x1f");
+    }
+
+    #[tokio::test]
+    async fn test_write_then_read_back() -> Result<()> {
+        // Minimal anvil: setStorageAt stores, getStorageAt reads
+        let storage: Arc<Mutex<HashMap<Slot, String>>> = Arc::default();
+        let node_storage = Arc::clone(&storage);
+        let url = mock_node(move |method, params| {
+            let Ok(mut storage) = node_storage.lock() else {
+                return serde_json::Value::Null;
+            };
+            let slot = params[1].as_str().and_then(|s| s.parse::<Slot>().ok());
+            match (method, slot) {
+                ("web3_clientVersion", _) => serde_json::json!("anvil/v0.2.0"),
+                ("anvil_setStorageAt", Some(slot)) => {
+                    storage.insert(slot, params[2].as_str().unwrap_or_default().to_string());
+                    serde_json::json!(true)
+                }
+                ("eth_getStorageAt", Some(slot)) => serde_json::json!(
+                    storage
+                        .get(&slot)
+                        .cloned()
+                        .unwrap_or_else(|| "0x0".to_string())
+                ),
+                _ => serde_json::Value::Null,
+            }
+        })
+        .await?;
+
+        let node = detect_dev_node(&url).await?;
+        assert_eq!(node, DevNode::Anvil);
+
+        let slot = Slot::from(3);
+        let value = Slot::from(1_000_000).0;
+        let token = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        assert_eq!(write_storage(&url, node, token, slot, value).await?, value);
+        assert!(
+            write_storage(&url, DevNode::Hardhat, token, Slot::from(4), value)
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+}